@@ -0,0 +1,87 @@
+//! Throughput of the in-process channel between the RPC loops, for large
+//! pastes and big `update` messages.
+//!
+//! Run with `cargo bench --bench channel`.
+
+#[allow(dead_code)]
+#[path = "../src/channel.rs"]
+mod channel;
+
+use std::io::{BufRead, Write};
+use std::thread;
+use std::time::Instant;
+
+/// Sends `message` `count` times, written in chunks of `chunk_size` bytes as
+/// when a message is split across several writes, and prints the
+/// throughput.
+fn bench(name: &str, message: &[u8], count: usize, chunk_size: usize) {
+    let (mut writer, mut reader) = channel::channel();
+
+    let start = Instant::now();
+    let consumer = thread::spawn(move || {
+        let mut received = 0;
+        let mut line = String::new();
+        loop {
+            line.clear();
+            match reader.read_line(&mut line).unwrap() {
+                0 => return received,
+                len => received += len,
+            }
+        }
+    });
+
+    for _ in 0..count {
+        for chunk in message.chunks(chunk_size) {
+            writer.write_all(chunk).unwrap();
+        }
+    }
+    writer.shutdown();
+
+    let received = consumer.join().unwrap();
+    let elapsed = start.elapsed().as_secs_f64();
+    assert_eq!(received, message.len() * count);
+    println!(
+        "{:<32} {:>8} messages {:>10.1} MB/s",
+        name,
+        count,
+        received as f64 / elapsed / 1_000_000.0
+    );
+}
+
+/// An `edit` notification inserting `len` bytes of text.
+fn paste(len: usize) -> Vec<u8> {
+    let chars = "lorem ipsum dolor sit amet\\n".repeat(len / 28 + 1);
+    format!(
+        "{{\"method\":\"edit\",\"params\":{{\"view_id\":\"view-id-1\",\"method\":\"paste\",\
+         \"params\":{{\"chars\":\"{}\"}}}}}}\n",
+        &chars[..len]
+    )
+    .into_bytes()
+}
+
+/// An `update` notification inserting `lines` styled lines.
+fn update(count: usize) -> Vec<u8> {
+    let line = "{\"text\":\"    let value = compute(first, second, third);\\n\",\
+                \"cursor\":[4],\"styles\":[0,7,2,4,5,3]}";
+    let lines = vec![line; count].join(",");
+    format!(
+        "{{\"method\":\"update\",\"params\":{{\"view_id\":\"view-id-1\",\"update\":{{\
+         \"ops\":[{{\"op\":\"ins\",\"n\":{},\"lines\":[{}]}}]}}}}}}\n",
+        count,
+        lines
+    )
+    .into_bytes()
+}
+
+fn main() {
+    let keystroke = paste(1);
+    bench("keystrokes", &keystroke, 100_000, keystroke.len());
+
+    let paste = paste(1_000_000);
+    bench("1 MB paste", &paste, 100, paste.len());
+    bench("1 MB paste, 8 KB writes", &paste, 100, 8192);
+
+    let update = update(10_000);
+    bench("10000 lines update", &update, 100, update.len());
+    bench("10000 lines update, 8 KB writes", &update, 100, 8192);
+}
@@ -0,0 +1,208 @@
+//! The in-process transport of the RPC loops, an mpsc channel carrying one
+//! message per line.
+//!
+//! The messages are passed as owned byte buffers. They are only validated as
+//! UTF-8 once, by the `Reader`, since xi-rpc reads them as strings.
+
+use std::io::{self, BufRead, Read, Write};
+use std::mem;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Arc;
+
+/// Called with each message sent or received on a channel, to record the
+/// traffic.
+pub type Tap = Arc<dyn Fn(&[u8]) + Send + Sync>;
+
+/// A message sent on the channels between the RPC loops.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Message {
+    /// A serialized JSON-RPC message, ending with its newline.
+    Data(Vec<u8>),
+    /// Stops the RPC loop reading the channel, once it handled the messages
+    /// sent before.
+    Shutdown,
+}
+
+/// Creates a channel whose `Writer` is the destination stream of an RPC loop
+/// and whose `Reader` is the source stream of another one.
+pub fn channel() -> (Writer, Reader) {
+    let (tx, rx) = mpsc::channel();
+    let writer = Writer {
+        tx,
+        pending: Vec::new(),
+        taps: Vec::new(),
+    };
+    let reader = Reader {
+        rx,
+        frame: Vec::new(),
+        pos: 0,
+        closed: false,
+        taps: Vec::new(),
+    };
+    (writer, reader)
+}
+
+/// Wraps an instance of `mpsc::Sender`, implementing `Write`.
+///
+/// The bytes written are sent once a whole line is written, a message split
+/// across several writes is sent as a single one.
+pub struct Writer {
+    tx: Sender<Message>,
+    /// The start of the message being written.
+    pending: Vec<u8>,
+    taps: Vec<Tap>,
+}
+
+impl Writer {
+    /// Calls `tap` with each message sent by this writer and its clones made
+    /// afterwards, after the taps added before.
+    pub fn add_tap(&mut self, tap: Tap) {
+        self.taps.push(tap);
+    }
+
+    /// Stops the RPC loop reading the other side of the channel.
+    pub fn shutdown(&self) {
+        // The loop is already stopped if the channel is closed.
+        let _ = self.tx.send(Message::Shutdown);
+    }
+
+    fn send(&self, message: Vec<u8>) -> io::Result<()> {
+        for tap in &self.taps {
+            tap(&message);
+        }
+        self.tx
+            .send(Message::Data(message))
+            .map_err(|_| io::Error::new(io::ErrorKind::BrokenPipe, "the channel is closed"))
+    }
+}
+
+/// The clone starts without the partial message of the writer, each writer
+/// writing whole messages.
+impl Clone for Writer {
+    fn clone(&self) -> Self {
+        Writer {
+            tx: self.tx.clone(),
+            pending: Vec::new(),
+            taps: self.taps.clone(),
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut rest = buf;
+        while let Some(end) = rest.iter().position(|&b| b == b'\n') {
+            let (line, tail) = rest.split_at(end + 1);
+            let message = if self.pending.is_empty() {
+                line.to_vec()
+            } else {
+                let mut message = mem::take(&mut self.pending);
+                message.extend_from_slice(line);
+                message
+            };
+            self.send(message)?;
+            rest = tail;
+        }
+
+        self.pending.extend_from_slice(rest);
+        Ok(buf.len())
+    }
+
+    /// Does nothing: a message is only complete with its newline.
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Wraps an instance of `mpsc::Receiver`, implementing `BufRead`.
+///
+/// The stream ends with the shutdown of the channel, or once every writer is
+/// dropped.
+pub struct Reader {
+    rx: Receiver<Message>,
+    /// The message being read, from `pos`.
+    frame: Vec<u8>,
+    pos: usize,
+    closed: bool,
+    taps: Vec<Tap>,
+}
+
+impl Reader {
+    /// Calls `tap` with each message received, after the taps added before.
+    pub fn add_tap(&mut self, tap: Tap) {
+        self.taps.push(tap);
+    }
+
+    /// Returns the rest of the message being read or the next one, `None`
+    /// at the end of the stream.
+    fn next_frame(&mut self) -> Option<Vec<u8>> {
+        if self.pos < self.frame.len() {
+            let mut frame = mem::take(&mut self.frame);
+            frame.drain(..self.pos);
+            self.pos = 0;
+            return Some(frame);
+        }
+
+        if self.closed {
+            return None;
+        }
+
+        match self.rx.recv() {
+            Ok(Message::Data(frame)) => {
+                for tap in &self.taps {
+                    tap(&frame);
+                }
+                Some(frame)
+            }
+            Ok(Message::Shutdown) | Err(_) => {
+                self.closed = true;
+                None
+            }
+        }
+    }
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl BufRead for Reader {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos >= self.frame.len() {
+            self.frame = self.next_frame().unwrap_or_default();
+            self.pos = 0;
+        }
+        Ok(&self.frame[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.frame.len());
+    }
+
+    /// Reads a whole message, as called by xi-rpc. The message is moved in
+    /// `buf` rather than copied when `buf` is empty.
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        // Reading nothing tells the RPC loop the stream is over, it stops
+        // after handling the messages it already read.
+        let frame = match self.next_frame() {
+            Some(frame) => frame,
+            None => return Ok(0),
+        };
+
+        let line = String::from_utf8(frame)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        let len = line.len();
+        if buf.is_empty() {
+            *buf = line;
+        } else {
+            buf.push_str(&line);
+        }
+        Ok(len)
+    }
+}
@@ -0,0 +1,77 @@
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How the line numbers are displayed in the gutter.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LineNumbers {
+    Off,
+    /// The number of each line.
+    #[default]
+    Absolute,
+    /// The distance of each line from the cursor line.
+    Relative,
+    /// Relative numbers, except for the cursor line which shows its number.
+    Hybrid,
+}
+
+impl LineNumbers {
+    /// Returns the mode following this one, used to toggle between them.
+    pub fn next(self) -> Self {
+        match self {
+            LineNumbers::Off => LineNumbers::Absolute,
+            LineNumbers::Absolute => LineNumbers::Relative,
+            LineNumbers::Relative => LineNumbers::Hybrid,
+            LineNumbers::Hybrid => LineNumbers::Off,
+        }
+    }
+}
+
+/// The settings of the frontend, xi-core keeps its own settings.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub line_numbers: LineNumbers,
+    /// Wrap the lines wider than the screen instead of scrolling
+    /// horizontally.
+    pub soft_wrap: bool,
+    /// The xi-core theme, xi-core picks its default one if none is set.
+    pub theme: Option<String>,
+    /// The xi-core binary to run in a child process, instead of the xi-core
+    /// embedded in the frontend. Overridden by `$XI_CORE`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub xi_core: Option<PathBuf>,
+
+    /// File where the config is saved, if any.
+    #[serde(skip)]
+    file_path: Option<PathBuf>,
+}
+
+impl Config {
+    /// Loads the config saved in `path`, or the default one if the file
+    /// doesn't exist yet. The config is saved back in `path` by `save`.
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut config: Config = match File::open(path) {
+            Ok(file) => serde_json::from_reader(file)?,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Config::default(),
+            Err(err) => return Err(err),
+        };
+
+        config.file_path = Some(path.to_path_buf());
+        Ok(config)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let file_path = match self.file_path {
+            Some(ref file_path) => file_path,
+            None => return Ok(()),
+        };
+
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer_pretty(File::create(file_path)?, self)?;
+        Ok(())
+    }
+}
@@ -1,121 +1,222 @@
-use std::io::{self, BufRead, Read, Write};
-use std::sync::mpsc::{channel, Receiver, Sender};
-use std::thread;
+use std::io::{self, Read, Write};
+use std::net::Shutdown;
+use std::os::unix::net::UnixStream;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread::{self, JoinHandle};
 
 use serde_json::Value;
 use xi_core_lib::XiCore;
 use xi_rpc::RpcLoop;
 
-/// Wraps an instance of `mpsc::Sender`, implementing `Write`.
-///
-/// This lets the tx side of an mpsc::channel serve as the destination
-/// stream for an RPC loop.
-pub struct Writer(Sender<String>);
-
-impl Write for Writer {
-    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
-        let s = String::from_utf8(buf.to_vec()).unwrap();
-        self.0
-            .send(s)
-            .map_err(|err| io::Error::new(io::ErrorKind::Other, format!("{:?}", err)))
-            .map(|_| buf.len())
+use crate::channel::{channel, Reader, Writer};
+use crate::error::Result;
+use crate::protocol::ClientNotification;
+use crate::ui::messages::Severity;
+
+pub struct ClientToClientWriter(Writer);
+
+impl ClientToClientWriter {
+    #[cfg(test)]
+    pub fn new(writer: Writer) -> Self {
+        ClientToClientWriter(writer)
     }
 
-    fn flush(&mut self) -> io::Result<()> {
+    /// Fails once the EventController stopped.
+    pub fn send_rpc_notification(&mut self, method: &str, params: &Value) -> Result<()> {
+        let mut raw_content = serde_json::to_vec(&json!({"method": method, "params": params}))?;
+        raw_content.push(b'\n');
+
+        self.0.write_all(&raw_content)?;
         Ok(())
     }
+
+    /// Stops the EventController, once it handled the notifications sent
+    /// before.
+    ///
+    /// The channel is shared by xi-core and the InputController, it can't be
+    /// closed by dropping the writer of the InputController.
+    pub fn shutdown(&mut self) {
+        self.0.shutdown();
+    }
 }
 
-/// Wraps an instance of `mpsc::Receiver`, providing convenience methods
-/// for parsing received messages.
-pub struct Reader(Receiver<String>);
+/// The xi-core instance, embedded in a thread, running in a child process or
+/// in a session server.
+pub struct CoreThread {
+    writer: Writer,
+    handle: JoinHandle<()>,
+}
 
-impl Read for Reader {
-    fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
-        unreachable!("didn't expect xi-rpc to call read");
+impl CoreThread {
+    /// Stops xi-core once it handled the messages sent before, and waits for
+    /// it to end.
+    pub fn shutdown(self) {
+        self.writer.shutdown();
+        if self.handle.join().is_err() {
+            error!("the xi-core thread panicked");
+        }
     }
 }
 
-// Note: we don't properly implement BufRead, only the stylized call patterns
-// used by xi-rpc.
-impl BufRead for Reader {
-    fn fill_buf(&mut self) -> io::Result<&[u8]> {
-        unreachable!("didn't expect xi-rpc to call fill_buf");
-    }
+/// Starts xi-core in a thread of the frontend.
+pub fn start_xi_core() -> (Writer, Reader, ClientToClientWriter, CoreThread) {
+    let mut core = XiCore::new();
 
-    fn consume(&mut self, _amt: usize) {
-        unreachable!("didn't expect xi-rpc to call consume");
-    }
+    let (client_to_core_writer, client_to_core_reader) = channel();
+    let shutdown_writer = client_to_core_writer.clone();
 
-    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
-        let event = match self.0.recv() {
-            Ok(s) => s,
-            Err(_) => return Ok(0),
-        };
+    let (core_to_client_writer, core_to_client_reader) = channel();
+    let client_to_client_writer = ClientToClientWriter(core_to_client_writer.clone());
 
-        if &event == r#"{"method":"command","params":{"method":"exit"}}"# {
-            // It receive a close commmand from the writer indicating the chan
-            // should be closes. The event is sent by the InputController when
-            // the user ask to quit the program.
-            //
-            // This method is required because the chan producers a shared between
-            // The InputController and the EventController threads and it's
-            // impossible for the InputController to close the EventController
-            // channel.
-            //
-            // When the Reader receives the command, it close the channel between
-            // the InputController which lead to the following steps in order:
-            // - The channel between the the InputController and the Core close itself.
-            // - The Core event loop stop itself safely.
-            // - The channel between the Core and the EventController close itself.
-            // - The the EventController event loop stop itself safely.
-            // - The main exit.
-            return Ok(0);
+    let mut core_event_loop = RpcLoop::new(core_to_client_writer);
+    let handle = thread::spawn(move || {
+        if let Err(err) = core_event_loop.mainloop(|| client_to_core_reader, &mut core) {
+            error!("xi-core stopped with an error: {:?}", err);
         }
+    });
 
-        buf.push_str(&event);
-        Ok(event.len())
-    }
+    (
+        client_to_core_writer,
+        core_to_client_reader,
+        client_to_client_writer,
+        CoreThread {
+            writer: shutdown_writer,
+            handle,
+        },
+    )
 }
 
-pub struct ClientToClientWriter(Writer);
-
-impl ClientToClientWriter {
-    pub fn send_rpc_notification(&mut self, method: &str, params: &Value) {
-        let raw_content = match serde_json::to_vec(&json!({"method": method, "params": params})) {
-            Ok(raw) => raw,
-            Err(err) => {
-                panic!("failed to create the notification {}: {}", method, err);
-                //return;
+/// Starts the xi-core binary `path` in a child process, speaking with it
+/// over its stdin and stdout.
+///
+/// The messages go through the same channels as with the embedded xi-core,
+/// the InputController keeps writing its notifications to the channel read
+/// by the EventController.
+pub fn spawn_xi_core(
+    path: &Path,
+) -> io::Result<(Writer, Reader, ClientToClientWriter, CoreThread)> {
+    let mut child = Command::new(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child.stdin.take().expect("the stdin of xi-core is piped");
+    let stdout = child.stdout.take().expect("the stdout of xi-core is piped");
+
+    let (client_to_core_writer, mut client_to_core_reader) = channel();
+    let shutdown_writer = client_to_core_writer.clone();
+
+    let (core_to_client_writer, core_to_client_reader) = channel();
+    let client_to_client_writer = ClientToClientWriter(core_to_client_writer.clone());
+
+    // xi-core closes its stdout when it exits, after the shutdown or when it
+    // crashed.
+    let forward = forward_lines(stdout, core_to_client_writer, move || {
+        let message = match child.wait() {
+            Ok(status) if status.success() => {
+                info!("xi-core stopped");
+                return String::from("xi-core stopped");
             }
+            Ok(status) => format!("xi-core stopped with {}", status),
+            Err(err) => format!("failed to wait for xi-core: {}", err),
         };
+        error!("{}", message);
+        message
+    });
+
+    // Forwards the messages sent to xi-core. Closing its stdin on shutdown
+    // stops xi-core once it handled them.
+    let handle = thread::spawn(move || {
+        write_messages(&mut client_to_core_reader, &mut stdin);
+        drop(stdin);
+
+        if forward.join().is_err() {
+            error!("the thread reading xi-core panicked");
+        }
+    });
 
-        match self.0.write(&raw_content) {
-            Ok(_) => (),
-            Err(err) => panic!("failed to send the notification {}: {}", method, err),
-        };
-    }
+    Ok((
+        client_to_core_writer,
+        core_to_client_reader,
+        client_to_client_writer,
+        CoreThread {
+            writer: shutdown_writer,
+            handle,
+        },
+    ))
 }
 
-pub fn start_xi_core() -> (Writer, Reader, ClientToClientWriter) {
-    let mut core = XiCore::new();
-
-    let (to_core_tx, to_core_rx) = channel();
-    let client_to_core_writer = Writer(to_core_tx);
-    let client_to_core_reader = Reader(to_core_rx);
-
-    let (from_core_tx, from_core_rx) = channel();
-    let core_to_client_writer = Writer(from_core_tx.clone());
-    let core_to_client_reader = Reader(from_core_rx);
-
-    let client_to_client_writer = ClientToClientWriter(Writer(from_core_tx));
-
-    let mut core_event_loop = RpcLoop::new(core_to_client_writer);
-    thread::spawn(move || core_event_loop.mainloop(|| client_to_core_reader, &mut core));
+/// Attaches to the session server listening on `socket_path`.
+///
+/// Stopping the returned `CoreThread` only detaches from the session, xi-core
+/// keeps running in the server.
+pub fn connect_xi_core(
+    socket_path: &Path,
+) -> io::Result<(Writer, Reader, ClientToClientWriter, CoreThread)> {
+    let mut stream = UnixStream::connect(socket_path)?;
+
+    let (client_to_core_writer, mut client_to_core_reader) = channel();
+    let shutdown_writer = client_to_core_writer.clone();
+
+    let (core_to_client_writer, core_to_client_reader) = channel();
+    let client_to_client_writer = ClientToClientWriter(core_to_client_writer.clone());
+
+    // The server closes the socket when another frontend attaches.
+    forward_lines(stream.try_clone()?, core_to_client_writer, || {
+        String::from("detached: another frontend attached")
+    });
+
+    let handle = thread::spawn(move || {
+        write_messages(&mut client_to_core_reader, &mut stream);
+        if let Err(err) = stream.shutdown(Shutdown::Both) {
+            error!("failed to close the session socket: {}", err);
+        }
+    });
 
-    (
+    Ok((
         client_to_core_writer,
         core_to_client_reader,
         client_to_client_writer,
-    )
+        CoreThread {
+            writer: shutdown_writer,
+            handle,
+        },
+    ))
+}
+
+/// Forwards the messages read from `source`, one per line, to `writer` in a
+/// new thread, until `source` is closed. The `writer` sends each message once
+/// its whole line is read.
+///
+/// Once `source` is closed, xi-core is gone: the EventController shows the
+/// message returned by `stopped` and stops, unless it stopped first.
+fn forward_lines<R, F>(mut source: R, mut writer: Writer, stopped: F) -> JoinHandle<()>
+where
+    R: Read + Send + 'static,
+    F: FnOnce() -> String + Send + 'static,
+{
+    thread::spawn(move || {
+        if let Err(err) = io::copy(&mut source, &mut writer) {
+            // Also the case once the reader of the channel is stopped.
+            warn!("stopped forwarding the messages: {}", err);
+        }
+
+        let message = stopped();
+        let mut writer = ClientToClientWriter(writer);
+        let notification = ClientNotification::ShowMessage {
+            message,
+            severity: Severity::Error,
+            sticky: true,
+        };
+        if notification.send(&mut writer).is_ok() {
+            writer.shutdown();
+        }
+    })
+}
+
+/// Writes the messages read from `reader` to `sink`, until the shutdown.
+fn write_messages<W: Write>(reader: &mut Reader, sink: &mut W) {
+    if let Err(err) = io::copy(reader, sink) {
+        error!("failed to write a message: {}", err);
+    }
 }
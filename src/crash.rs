@@ -0,0 +1,121 @@
+//! Restores the terminal when the frontend panics, and writes a crash report
+//! with the last messages exchanged with xi-core and the open views.
+
+use std::backtrace::Backtrace;
+use std::collections::{BTreeMap, VecDeque};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::{Arc, Mutex};
+
+use crate::channel::Tap;
+use crate::trace::{Direction, Record};
+use crate::ui::terminal;
+
+/// The number of messages kept for the crash report.
+const TRAFFIC_LEN: usize = 200;
+
+struct State {
+    /// The last messages, as `(time, direction, message)`. They are only
+    /// parsed for the report.
+    traffic: VecDeque<(String, Direction, Vec<u8>)>,
+    /// The path of each view, by view id.
+    views: BTreeMap<String, Option<String>>,
+}
+
+static STATE: Mutex<State> = Mutex::new(State {
+    traffic: VecDeque::new(),
+    views: BTreeMap::new(),
+});
+
+/// Installs the panic hook. It restores the terminal, logs the panic, writes
+/// a crash report in `report_dir` and stops the frontend.
+///
+/// The frontend is stopped since its other threads would wait forever for
+/// the one which panicked.
+pub fn install(report_dir: PathBuf) {
+    panic::set_hook(Box::new(move |info| {
+        terminal::restore_terminal();
+
+        let panic = info.to_string();
+        let backtrace = Backtrace::force_capture();
+        error!("{}\n{}", panic, backtrace);
+
+        match write_report(&report_dir, &panic, &backtrace) {
+            Ok(report_path) => {
+                eprintln!("{}\nThe crash report is in {}", panic, report_path.display())
+            }
+            Err(err) => {
+                error!("failed to write the crash report: {}", err);
+                eprintln!("{}", panic);
+            }
+        }
+
+        process::exit(101);
+    }));
+}
+
+/// Returns a tap keeping the last messages going in `direction` for the
+/// crash report.
+pub fn tap(direction: Direction) -> Tap {
+    Arc::new(move |message| {
+        let mut state = STATE.lock().unwrap();
+        if state.traffic.len() == TRAFFIC_LEN {
+            state.traffic.pop_front();
+        }
+        let time = chrono::Local::now().to_rfc3339();
+        state.traffic.push_back((time, direction, message.to_vec()));
+    })
+}
+
+/// Lists the view `view_id` in the crash report.
+pub fn track_view(view_id: &str, path: Option<&str>) {
+    let mut state = STATE.lock().unwrap();
+    state.views.insert(view_id.to_string(), path.map(str::to_string));
+}
+
+pub fn forget_view(view_id: &str) {
+    STATE.lock().unwrap().views.remove(view_id);
+}
+
+pub fn forget_views() {
+    STATE.lock().unwrap().views.clear();
+}
+
+/// Writes the report in a new file of `report_dir`, and returns its path.
+///
+/// The messages are written as the lines of a trace: once copied to a file,
+/// they can be replayed with `--replay`.
+fn write_report(report_dir: &Path, panic: &str, backtrace: &Backtrace) -> io::Result<PathBuf> {
+    fs::create_dir_all(report_dir)?;
+    let now = chrono::Local::now();
+    let report_path = report_dir.join(format!("crash-{}.txt", now.format("%Y%m%d-%H%M%S")));
+    let mut report = File::create(&report_path)?;
+
+    writeln!(report, "xi-example crashed at {}", now.to_rfc3339())?;
+    writeln!(report, "\n{}\n\n{}", panic, backtrace)?;
+
+    // The lock is held by this thread if it panicked while recording a
+    // message, the report is written without the state then.
+    let state = match STATE.try_lock() {
+        Ok(state) => state,
+        Err(_) => return Ok(report_path),
+    };
+
+    writeln!(report, "\nOpen views:")?;
+    for (view_id, path) in &state.views {
+        let path = path.as_deref().unwrap_or("<no path>");
+        writeln!(report, "{} {}", view_id, path)?;
+    }
+
+    writeln!(report, "\nLast messages:")?;
+    for (time, direction, message) in &state.traffic {
+        let record = Record::new(time.clone(), *direction, message);
+        serde_json::to_writer(&mut report, &record)?;
+        writeln!(report)?;
+    }
+
+    Ok(report_path)
+}
@@ -0,0 +1,80 @@
+//! The errors of the frontend, shown to the user on the message line when
+//! they don't stop it.
+
+use std::fmt;
+use std::io;
+
+use serde_json::Value;
+use xi_rpc::RemoteError;
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    /// Also returned once the EventController stopped: nothing can be shown
+    /// anymore.
+    Io(io::Error),
+    Json(serde_json::Error),
+    /// xi-core failed to answer a request, `request` describing it for the
+    /// user, like "cut the selection".
+    Core {
+        request: String,
+        error: xi_rpc::Error,
+    },
+    /// xi-core answered a request with a value of the wrong type.
+    InvalidResponse { request: String, response: Value },
+}
+
+impl Error {
+    pub fn core<S: Into<String>>(request: S, error: xi_rpc::Error) -> Self {
+        Error::Core {
+            request: request.into(),
+            error,
+        }
+    }
+
+    pub fn invalid_response<S: Into<String>>(request: S, response: Value) -> Self {
+        Error::InvalidResponse {
+            request: request.into(),
+            response,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "{}", err),
+            Error::Json(err) => write!(f, "invalid message: {}", err),
+            Error::Core { request, error } => {
+                write!(f, "failed to {}: ", request)?;
+                match error {
+                    xi_rpc::Error::Io(err) => write!(f, "{}", err),
+                    xi_rpc::Error::PeerDisconnect => write!(f, "xi-core disconnected"),
+                    xi_rpc::Error::RemoteError(RemoteError::Custom { message, .. }) => {
+                        write!(f, "{}", message)
+                    }
+                    xi_rpc::Error::RemoteError(err) => write!(f, "{:?}", err),
+                    xi_rpc::Error::InvalidResponse => write!(f, "invalid response"),
+                }
+            }
+            Error::InvalidResponse { request, response } => {
+                write!(f, "failed to {}: unexpected response {}", request, response)
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
@@ -1,45 +1,903 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use std::time::{Duration, Instant};
 
+use serde_json::Value;
+use xi_rpc::Peer;
+
+use crate::config::Config;
+use crate::crash;
+use crate::protocol::{
+    ClientNotification, ClientRequest, ConfigDomain, CoreNotification, EditMethod, GestureType,
+    MeasureWidthItem, PluginInfo, StatusItem, Theme, Update,
+};
+use crate::session::{SavedView, Session};
+use crate::swap::SwapDir;
+use crate::ui::messages::{History, Message, Severity, MESSAGE_TIMEOUT};
+use crate::ui::picker::{Picker, PickerKind};
+use crate::ui::status_bar::{Alignment, StatusBar};
+use crate::ui::style::Styles;
+use crate::ui::terminal::Terminal;
+use crate::ui::view::{self, Cursor, Settings, View};
+
+/// The JSON-RPC error code of the unknown methods.
+const METHOD_NOT_FOUND: i64 = -32601;
+
+/// The timer token of the swap files writing.
+const SWAP_TOKEN: usize = 1;
+/// The delay between a change and the writing of the swap files.
+const SWAP_INTERVAL: Duration = Duration::from_secs(4);
+/// The delay before writing the swap files again, once xi-core sent the
+/// lines missing from a view.
+const SWAP_RETRY: Duration = Duration::from_millis(500);
+
+/// The timer token of the dismissal of the messages.
+const MESSAGE_TOKEN: usize = 2;
 
 pub struct EventController {
-    //styles: Rc<RefCell<Box<dyn Styles>>>,
-    //views: HashMap<ViewID, View>,
+    config: Config,
+    terminal: Box<dyn Terminal>,
+    styles: Styles,
+    views: HashMap<String, View>,
     //layout: Box<dyn Layout>,
-    //status_bar: StatusBar,
-    //current_view: String,
+    status_bar: StatusBar,
+    picker: Option<Picker>,
+    /// The messages shown so far.
+    messages: History,
+    /// The messages are listed over the current view.
+    messages_shown: bool,
+    current_view: Option<String>,
+    /// The languages xi-core can highlight.
+    languages: Vec<String>,
+    /// The themes available in xi-core.
+    themes: Vec<String>,
+    /// The theme used by xi-core.
+    theme: Option<String>,
+    /// Sends the themes to the InputController, which completes them on the
+    /// command line.
+    themes_sender: Option<Sender<Vec<String>>>,
+    swap: Option<SwapDir>,
+    /// The writing of the swap files is scheduled.
+    swap_scheduled: bool,
+    /// The session file saved on exit and by `:mksession`.
+    session_file: Option<PathBuf>,
+    /// The time the message of the status bar is dismissed, `None` if it is
+    /// kept until replaced.
+    message_expiry: Option<Instant>,
 }
 
 impl EventController {
-    pub fn new() -> Self {
-      EventController {}
+    pub fn new(config: Config, terminal: Box<dyn Terminal>) -> Self {
+      EventController {
+        config,
+        terminal,
+        styles: Styles::new(),
+        views: HashMap::new(),
+        status_bar: StatusBar::new(),
+        picker: None,
+        messages: History::new(),
+        messages_shown: false,
+        current_view: None,
+        languages: Vec::new(),
+        themes: Vec::new(),
+        theme: None,
+        themes_sender: None,
+        swap: None,
+        swap_scheduled: false,
+        session_file: None,
+        message_expiry: None,
+      }
+    }
+
+    /// Writes the text of the modified views to swap files in `swap_dir`.
+    pub fn use_swap_dir(&mut self, swap_dir: SwapDir) {
+        self.swap = Some(swap_dir);
+    }
+
+    /// Sends the themes available in xi-core to `themes_sender` once they
+    /// are known.
+    pub fn send_themes_to(&mut self, themes_sender: Sender<Vec<String>>) {
+        self.themes_sender = Some(themes_sender);
+    }
+
+    /// Saves the session to `session_file` on exit and with `:mksession`.
+    pub fn use_session_file(&mut self, session_file: PathBuf) {
+        self.session_file = Some(session_file);
+    }
+
+    /// Returns the views with a path and the theme. The views are sorted by
+    /// path, the current one being the last to be displayed once reopened.
+    fn session(&self) -> Session {
+        let mut views: Vec<(bool, SavedView)> = self
+            .views
+            .iter()
+            .filter_map(|(view_id, view)| {
+                let path = view.path.clone()?;
+                let (first_line, first_col) = view.scroll_offset();
+                let saved = SavedView {
+                    path,
+                    line: view.cursor.line,
+                    col: view.cursor.col,
+                    first_line,
+                    first_col,
+                };
+                Some((self.current_view.as_ref() == Some(view_id), saved))
+            })
+            .collect();
+        views.sort_by(|(a_current, a), (b_current, b)| {
+            (a_current, &a.path).cmp(&(b_current, &b.path))
+        });
+
+        Session {
+            theme: self.theme.clone(),
+            views: views.into_iter().map(|(_, view)| view).collect(),
+        }
+    }
+
+    /// Saves the session to the session file, called once the
+    /// EventController is stopped, before the views are closed.
+    pub fn save_session(&self) {
+        let session_file = match self.session_file {
+            Some(ref session_file) => session_file,
+            None => return,
+        };
+
+        if let Err(err) = self.session().save(session_file) {
+            error!("failed to save the session to {}: {}", session_file.display(), err);
+        }
+    }
+
+    fn make_session(&mut self, ctx: &xi_rpc::RpcCtx, path: Option<String>) {
+        let session_file = match path {
+            Some(ref path) => Path::new(path),
+            None => match self.session_file {
+                Some(ref session_file) => session_file.as_path(),
+                None => {
+                    self.notify(ctx, Severity::Error, String::from("no session file"));
+                    return;
+                }
+            },
+        };
+
+        let (severity, message) = match self.session().save(session_file) {
+            Ok(()) => (Severity::Info, format!("session saved to {}", session_file.display())),
+            Err(err) => {
+                let message =
+                    format!("failed to save the session to {}: {}", session_file.display(), err);
+                (Severity::Error, message)
+            }
+        };
+        self.notify(ctx, severity, message);
+    }
+
+    /// Restores the position of a view reopened from a session. The view
+    /// scrolls to `first_line` and `first_col`, and keeps them once xi-core
+    /// moved the cursor.
+    fn restore_view(
+        &mut self,
+        ctx: &xi_rpc::RpcCtx,
+        view_id: &str,
+        cursor: Cursor,
+        (first_line, first_col): (usize, usize),
+    ) {
+        self.view_mut(view_id).restore_position(cursor, first_line, first_col);
+        move_cursor(ctx, view_id, cursor);
+    }
+
+    fn render(&mut self) {
+        let views = &self.views;
+        let view = match self.current_view.as_ref().and_then(|id| views.get(id)) {
+            Some(view) => view,
+            None => return,
+        };
+
+        view.render(&mut *self.terminal, &self.config, &self.styles);
+        if self.messages_shown {
+            self.messages.render(&mut *self.terminal);
+        } else if let Some(ref picker) = self.picker {
+            picker.render(&mut *self.terminal);
+        }
+        self.status_bar.render(&mut *self.terminal);
+        if let Err(err) = self.terminal.flush() {
+            error!("failed to render the view: {}", err);
+        }
+    }
+
+    /// Fits the current view to the terminal, asking xi-core for the lines
+    /// which became visible and to wrap the lines at the new width.
+    fn fit_view(&mut self, ctx: &xi_rpc::RpcCtx) {
+        let (columns, rows) = self.terminal.size();
+        let view_id = match self.current_view {
+            Some(ref view_id) => view_id,
+            None => return,
+        };
+        let view = match self.views.get_mut(view_id) {
+            Some(view) => view,
+            None => return,
+        };
+
+        let gutter_width = view.gutter_width(self.config.line_numbers);
+        let width = columns.saturating_sub(gutter_width) as usize;
+
+        let wrap_width = if self.config.soft_wrap { width } else { 0 };
+        if view.wrap_width != Some(wrap_width) {
+            view.wrap_width = Some(wrap_width);
+            CoreNotification::ModifyUserConfig {
+                domain: ConfigDomain::UserOverride(view_id.clone()),
+                changes: json!({ "wrap_width": wrap_width }),
+            }
+            .send(ctx.get_peer().as_ref());
+        }
+
+        // The last row is used by the status bar.
+        view.resize(width, rows.saturating_sub(1) as usize);
+        if let Some((first, last)) = view.scroll_region_change() {
+            CoreNotification::edit(view_id, EditMethod::Scroll(first, last))
+                .send(ctx.get_peer().as_ref());
+        }
+    }
+
+    /// Closes the views, called once the EventController is stopped. Their
+    /// swap files are removed, the changes left unsaved were given up.
+    pub fn close_views(&mut self, core: &dyn Peer) {
+        for (view_id, view) in &self.views {
+            CoreNotification::CloseView {
+                view_id: view_id.clone(),
+            }
+            .send(core);
+
+            if let (Some(swap), Some(path), true) = (&self.swap, &view.path, view.has_swap) {
+                if let Err(err) = swap.remove(path) {
+                    error!("failed to remove the swap file of {}: {}", path, err);
+                }
+            }
+        }
+        self.views.clear();
+        self.current_view = None;
+        crash::forget_views();
+    }
+
+    /// Returns the view targeted by a notification, creating it the first
+    /// time xi-core talks about it.
+    fn view_mut(&mut self, view_id: &str) -> &mut View {
+        self.views.entry(view_id.to_string()).or_default()
+    }
+
+    fn handle_cursor_move(&mut self, view_id: &str, line: usize, col: usize) {
+        self.view_mut(view_id).set_cursor(Cursor { line, col });
+    }
+
+    fn handle_content_update(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str, update: &Update) {
+        self.view_mut(view_id).update(update);
+        self.schedule_swap(ctx, SWAP_INTERVAL);
+    }
+
+    fn schedule_swap(&mut self, ctx: &xi_rpc::RpcCtx, after: Duration) {
+        if self.swap.is_none() || self.swap_scheduled {
+            return;
+        }
+
+        self.swap_scheduled = true;
+        ctx.get_peer().schedule_timer(Instant::now() + after, SWAP_TOKEN);
+    }
+
+    /// Writes the swap files of the modified views, and removes the ones of
+    /// the views saved since.
+    fn write_swap_files(&mut self, ctx: &xi_rpc::RpcCtx) {
+        self.swap_scheduled = false;
+        let swap = match self.swap {
+            Some(ref swap) => swap,
+            None => return,
+        };
+
+        let mut incomplete = false;
+        let mut errors = Vec::new();
+        for (view_id, view) in &mut self.views {
+            let path = match view.path {
+                Some(ref path) => path,
+                None => continue,
+            };
+
+            if view.pristine {
+                if view.has_swap {
+                    if let Err(err) = swap.remove(path) {
+                        error!("failed to remove the swap file of {}: {}", path, err);
+                    }
+                    view.has_swap = false;
+                }
+                continue;
+            }
+            if !view.swap_outdated {
+                continue;
+            }
+
+            match view.lines.text() {
+                Some(text) => match swap.write(path, &text) {
+                    Ok(()) => {
+                        view.swap_outdated = false;
+                        view.has_swap = true;
+                    }
+                    Err(err) => {
+                        let message = format!("failed to write the swap file of {}: {}", path, err);
+                        error!("{}", message);
+                        errors.push(message);
+                    }
+                },
+                // xi-core only sends the lines displayed, the swap file is
+                // written once it sent the other ones.
+                None => {
+                    let request = EditMethod::RequestLines(0, view.lines.height());
+                    CoreNotification::edit(view_id, request).send(ctx.get_peer().as_ref());
+                    incomplete = true;
+                }
+            }
+        }
+
+        for error in errors {
+            self.notify(ctx, Severity::Error, error);
+        }
+        if incomplete {
+            self.schedule_swap(ctx, SWAP_RETRY);
+        }
+    }
+
+    fn set_path_for_view(&mut self, view_id: &str, path: Option<String>) {
+        crash::track_view(view_id, path.as_deref());
+        self.view_mut(view_id).path = path;
+
+        // The last opened view is the one displayed.
+        self.current_view = Some(view_id.to_string());
+        self.show_language();
+    }
+
+    /// Closes the view `view_id`, the InputController showing another view
+    /// next.
+    fn close_view(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        CoreNotification::CloseView {
+            view_id: view_id.to_string(),
+        }
+        .send(ctx.get_peer().as_ref());
+
+        crash::forget_view(view_id);
+        self.views.remove(view_id);
+        if self.current_view.as_deref() == Some(view_id) {
+            self.current_view = None;
+        }
+    }
+
+    fn write_to_file(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        let file_path = match self.view_mut(view_id).path.clone() {
+            Some(path) => path,
+            None => {
+                error!("no path to write the view {} to", view_id);
+                self.notify(ctx, Severity::Error, String::from("no file name to write to"));
+                return;
+            }
+        };
+
+        CoreNotification::Save {
+            view_id: view_id.to_string(),
+            file_path,
+        }
+        .send(ctx.get_peer().as_ref());
+    }
+
+    fn handle_new_status_item(&mut self, item: &StatusItem) {
+        self.status_bar.add_item(&item.key, &item.value, item.alignment);
+    }
+
+    fn update_status_item(&mut self, key: &str, value: &str) {
+        self.status_bar.update_item(key, value);
+    }
+
+    fn remove_status_item(&mut self, key: &str) {
+        self.status_bar.remove_item(key);
+    }
+
+    /// Shows `message` on the status bar, an empty message clearing it. The
+    /// messages which are not sticky are kept in the history, and dismissed
+    /// after `MESSAGE_TIMEOUT`.
+    fn show_message(
+        &mut self,
+        ctx: &xi_rpc::RpcCtx,
+        severity: Severity,
+        message: String,
+        sticky: bool,
+    ) {
+        if message.is_empty() {
+            self.status_bar.clear_message();
+            self.message_expiry = None;
+            return;
+        }
+
+        self.status_bar.set_message(severity, &message);
+        if sticky {
+            self.message_expiry = None;
+            return;
+        }
+
+        self.messages.record(Message::new(severity, message));
+        let expiry = Instant::now() + MESSAGE_TIMEOUT;
+        self.message_expiry = Some(expiry);
+        ctx.get_peer().schedule_timer(expiry, MESSAGE_TOKEN);
+    }
+
+    fn notify(&mut self, ctx: &xi_rpc::RpcCtx, severity: Severity, message: String) {
+        self.show_message(ctx, severity, message, false);
+    }
+
+    /// Clears the message of the status bar once it expired, returns true
+    /// if it was cleared. A newer message may have postponed it.
+    fn dismiss_message(&mut self) -> bool {
+        match self.message_expiry {
+            Some(expiry) if expiry <= Instant::now() => {
+                self.status_bar.clear_message();
+                self.message_expiry = None;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Lists the messages until `hide_messages`, the InputController waiting
+    /// for a key meanwhile.
+    fn show_messages(&mut self, ctx: &xi_rpc::RpcCtx) {
+        self.messages_shown = true;
+        self.show_message(ctx, Severity::Info, String::from("type any key to go back"), true);
+    }
+
+    fn hide_messages(&mut self, ctx: &xi_rpc::RpcCtx) {
+        self.messages_shown = false;
+        self.show_message(ctx, Severity::Info, String::new(), false);
+    }
+
+    fn handle_alert(&mut self, ctx: &xi_rpc::RpcCtx, message: String) {
+        warn!("xi-core alert: {}", message);
+        self.notify(ctx, Severity::Error, message);
+    }
+
+    fn update_command_line(&mut self, command_line: Option<String>) {
+        if command_line.is_some() {
+            self.status_bar.clear_message();
+            self.message_expiry = None;
+        }
+        self.status_bar.set_command_line(command_line);
+    }
+
+    fn handle_available_plugins(&mut self, view_id: &str, plugins: Vec<PluginInfo>) {
+        self.view_mut(view_id).plugins = plugins
+            .into_iter()
+            .map(|plugin| (plugin.name, plugin.running))
+            .collect();
+    }
+
+    fn handle_plugin_started(&mut self, view_id: &str, name: String) {
+        self.status_bar.remove_item(&format!("plugin-{}", name));
+        self.view_mut(view_id).plugins.insert(name, true);
+    }
+
+    fn handle_plugin_stopped(
+        &mut self,
+        ctx: &xi_rpc::RpcCtx,
+        view_id: &str,
+        name: String,
+        code: i64,
+    ) {
+        // A plugin stopped on request exits with 0, anything else is a crash.
+        if code != 0 {
+            let message = format!("plugin {} crashed with the code {}", name, code);
+            error!("{}", message);
+            self.notify(ctx, Severity::Error, message);
+            self.status_bar.add_item(
+                &format!("plugin-{}", name),
+                &format!("plugin {} crashed ({})", name, code),
+                Alignment::Right,
+            );
+        }
+
+        self.view_mut(view_id).plugins.insert(name, false);
+    }
+
+    fn list_plugins(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        let view = self.view_mut(view_id);
+        let message = if view.plugins.is_empty() {
+            String::from("no plugin available")
+        } else {
+            view.plugins
+                .iter()
+                .map(|(name, running)| {
+                    if *running {
+                        format!("{} (running)", name)
+                    } else {
+                        name.clone()
+                    }
+                })
+                .collect::<Vec<String>>()
+                .join(", ")
+        };
+
+        self.notify(ctx, Severity::Info, message);
+    }
+
+    fn handle_available_languages(&mut self, languages: Vec<String>) {
+        self.languages = languages;
+    }
+
+    fn handle_language_changed(&mut self, view_id: &str, language: String) {
+        self.view_mut(view_id).language = Some(language);
+        self.show_language();
+    }
+
+    /// Shows the language of the current view in the status bar.
+    fn show_language(&mut self) {
+        let views = &self.views;
+        let language = self
+            .current_view
+            .as_ref()
+            .and_then(|id| views.get(id))
+            .and_then(|view| view.language.clone());
+
+        match language {
+            Some(language) => self.status_bar.add_item("language", &language, Alignment::Right),
+            None => self.status_bar.remove_item("language"),
+        }
+    }
+
+    fn open_picker(&mut self, view_id: &str, kind: PickerKind) {
+        let (items, current) = match kind {
+            PickerKind::Language => {
+                let current = self.views.get(view_id).and_then(|view| view.language.clone());
+                (self.languages.clone(), current)
+            }
+            PickerKind::Theme => (self.themes.clone(), self.theme.clone()),
+        };
+
+        self.picker = Some(Picker::new(kind, view_id, items, current.as_deref()));
+    }
+
+    fn picker_move(&mut self, ctx: &xi_rpc::RpcCtx, delta: i64) {
+        let picker = match self.picker {
+            Some(ref mut picker) => picker,
+            None => return,
+        };
+
+        picker.move_by(delta);
+        if picker.kind == PickerKind::Theme {
+            if let Some(theme) = picker.selected() {
+                set_theme(ctx, theme);
+            }
+        }
+    }
+
+    fn picker_select(&mut self, ctx: &xi_rpc::RpcCtx) {
+        let picker = match self.picker.take() {
+            Some(picker) => picker,
+            None => return,
+        };
+        let item = match picker.selected() {
+            Some(item) => item,
+            None => return,
+        };
+
+        match picker.kind {
+            PickerKind::Language => CoreNotification::SetLanguage {
+                view_id: picker.view_id.clone(),
+                language_id: item.to_string(),
+            }
+            .send(ctx.get_peer().as_ref()),
+            PickerKind::Theme => self.select_theme(ctx, item),
+        }
+    }
+
+    fn picker_cancel(&mut self, ctx: &xi_rpc::RpcCtx) {
+        let picker = match self.picker.take() {
+            Some(picker) => picker,
+            None => return,
+        };
+
+        // Restores the theme used before the preview.
+        if let (PickerKind::Theme, Some(ref theme)) = (picker.kind, picker.initial) {
+            set_theme(ctx, theme);
+        }
+    }
+
+    fn handle_available_themes(&mut self, ctx: &xi_rpc::RpcCtx, themes: Vec<String>) {
+        // xi-core starts with its default theme, the one saved in the config
+        // is restored once it is known to exist.
+        match self.config.theme {
+            Some(ref theme) if themes.contains(theme) => set_theme(ctx, theme),
+            Some(ref theme) => {
+                let message = format!("the theme {} is not available", theme);
+                warn!("{}", message);
+                self.notify(ctx, Severity::Warning, message);
+            }
+            None => (),
+        }
+
+        if let Some(ref themes_sender) = self.themes_sender {
+            // The InputController is gone once the frontend stops.
+            let _ = themes_sender.send(themes.clone());
+        }
+        self.themes = themes;
+    }
+
+    fn handle_theme_changed(&mut self, name: String, theme: &Theme) {
+        self.theme = Some(name);
+        self.styles.set_theme(theme);
+    }
+
+    /// Uses `theme` and saves it in the config.
+    fn select_theme(&mut self, ctx: &xi_rpc::RpcCtx, theme: &str) {
+        set_theme(ctx, theme);
+
+        self.config.theme = Some(theme.to_string());
+        if let Err(err) = self.config.save() {
+            let message = format!("failed to save the config: {}", err);
+            error!("{}", message);
+            self.notify(ctx, Severity::Error, message);
+        }
+    }
+
+    fn handle_config_changed(&mut self, view_id: &str, changes: &Value) {
+        self.view_mut(view_id).settings.apply(changes);
+    }
+
+    /// Shows a setting of the view `view_id`, or of a view using `language`.
+    /// xi-core only sends the settings in effect in each view, the general
+    /// ones being the settings of the view unless they are overridden.
+    fn show_setting(
+        &mut self,
+        ctx: &xi_rpc::RpcCtx,
+        view_id: &str,
+        language: Option<&str>,
+        key: &str,
+    ) {
+        let view = match language {
+            None => self.views.get(view_id),
+            Some(language) => self
+                .views
+                .get(view_id)
+                .into_iter()
+                .chain(self.views.values())
+                .find(|view| view.language.as_deref() == Some(language)),
+        };
+
+        let message = match (view, language) {
+            (Some(view), _) => match view.settings.get(key) {
+                Some(value) => format!("{}={}", key, value),
+                None => format!("{} is not set", key),
+            },
+            (None, Some(language)) => format!("no view uses the language {}", language),
+            (None, None) => format!("{} is not set", key),
+        };
+        self.notify(ctx, Severity::Info, message);
+    }
+
+    /// Returns the number of columns taken by each string of the request,
+    /// the styles don't change the width of the text in a terminal.
+    fn measure_width(&self, items: &[MeasureWidthItem]) -> Value {
+        let views = &self.views;
+        let tab_size = match self.current_view.as_ref().and_then(|id| views.get(id)) {
+            Some(view) => view.settings.tab_size(),
+            None => Settings::default().tab_size(),
+        };
+
+        let widths: Vec<Vec<usize>> = items
+            .iter()
+            .map(|item| {
+                item.strings
+                    .iter()
+                    .map(|text| view::text_width(text, tab_size))
+                    .collect()
+            })
+            .collect();
+
+        json!(widths)
+    }
+
+    fn toggle_line_numbers(&mut self) {
+        self.config.line_numbers = self.config.line_numbers.next();
+    }
+
+    fn toggle_soft_wrap(&mut self) {
+        self.config.soft_wrap = !self.config.soft_wrap;
+    }
+
+    fn push_jump(&mut self, view_id: &str) {
+        let view = self.view_mut(view_id);
+        let cursor = view.cursor;
+        view.jumps.push(cursor);
+    }
+
+    fn jump_back(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        let view = self.view_mut(view_id);
+        let cursor = view.cursor;
+
+        if let Some(target) = view.jumps.back(cursor) {
+            move_cursor(ctx, view_id, target);
+        }
+    }
+
+    fn jump_forward(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        if let Some(target) = self.view_mut(view_id).jumps.forward() {
+            move_cursor(ctx, view_id, target);
+        }
+    }
+
+    fn move_to_first_non_blank(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        if let Some(target) = self.view_mut(view_id).first_non_blank() {
+            move_cursor(ctx, view_id, target);
+        }
+    }
+
+    fn move_to_matching_bracket(&mut self, ctx: &xi_rpc::RpcCtx, view_id: &str) {
+        let view = self.view_mut(view_id);
+
+        if let Some(target) = view.matching_bracket() {
+            let cursor = view.cursor;
+            view.jumps.push(cursor);
+            move_cursor(ctx, view_id, target);
+        }
     }
 }
 
+fn set_theme(ctx: &xi_rpc::RpcCtx, theme: &str) {
+    CoreNotification::SetTheme {
+        theme_name: theme.to_string(),
+    }
+    .send(ctx.get_peer().as_ref());
+}
+
+/// Moves the cursor of the view `view_id` to `target`.
+fn move_cursor(ctx: &xi_rpc::RpcCtx, view_id: &str, target: Cursor) {
+    let gesture = EditMethod::Gesture {
+        line: target.line,
+        col: target.col,
+        ty: GestureType::PointSelect,
+    };
+    CoreNotification::edit(view_id, gesture).send(ctx.get_peer().as_ref());
+}
+
 impl xi_rpc::Handler for EventController {
     type Notification = xi_rpc::RpcCall;
     type Request = xi_rpc::RpcCall;
 
     fn handle_notification(&mut self, ctx: &xi_rpc::RpcCtx, rpc: Self::Notification) {
-        match rpc.method.as_str() {
-            //"add_status_item" => self.handle_new_status_item(&rpc.params),
-            //"update_status_item" => self.update_status_item(&rpc.params),
-            //"plugin_started" => debug!("{}: -> {}", &rpc.method, &rpc.params),
-            //"available_languages" => debug!("{}", &rpc.method),
-            //"available_themes" => debug!("{}", &rpc.method),
-            //"available_plugins" => debug!("{}", &rpc.method),
-            //"config_changed" => debug!("{}", &rpc.method),
-            //"def_style" => self.handle_style_change(&rpc.params),
-            //"language_changed" => debug!("{}", &rpc.method),
-            //"scroll_to" => self.handle_cursor_move(&ctx, &rpc.params),
-            //"update" => self.handle_content_update(&ctx, &rpc.params),
-            //"theme_changed" => debug!("{}", &rpc.method),
-            //"set_path_for_view" => self.set_path_for_view(&ctx, &rpc.params),
-            //"write_to_file" => self.write_to_file(&ctx, &rpc.params),
-            _ => warn!("unhandled notif \"{}\" -> {}", &rpc.method, &rpc.params),
+        if !ClientNotification::is_known_method(&rpc.method) {
+            warn!("unknown notification \"{}\" -> {}", &rpc.method, &rpc.params);
+            return;
+        }
+
+        let notification = json!({ "method": &rpc.method, "params": &rpc.params });
+        let notification: ClientNotification = match serde_json::from_value(notification) {
+            Ok(notification) => notification,
+            Err(err) => {
+                error!("invalid notification \"{}\" -> {}: {}", &rpc.method, &rpc.params, err);
+                return;
+            }
+        };
+
+        match notification {
+            ClientNotification::Unknown => unreachable!("the unknown methods are rejected above"),
+            ClientNotification::UpdateCmds { .. }
+            | ClientNotification::FindStatus { .. }
+            | ClientNotification::ReplaceStatus { .. } => {
+                debug!("ignored notification \"{}\"", &rpc.method)
+            }
+            ClientNotification::AddStatusItem(item) => self.handle_new_status_item(&item),
+            ClientNotification::UpdateStatusItem { key, value } => {
+                self.update_status_item(&key, &value)
+            }
+            ClientNotification::RemoveStatusItem { key } => self.remove_status_item(&key),
+            ClientNotification::PluginStarted { view_id, plugin } => {
+                self.handle_plugin_started(&view_id, plugin)
+            }
+            ClientNotification::PluginStopped { view_id, plugin, code } => {
+                self.handle_plugin_stopped(ctx, &view_id, plugin, code)
+            }
+            ClientNotification::AvailableLanguages { languages } => {
+                self.handle_available_languages(languages)
+            }
+            ClientNotification::AvailableThemes { themes } => {
+                self.handle_available_themes(ctx, themes)
+            }
+            ClientNotification::AvailablePlugins { view_id, plugins } => {
+                self.handle_available_plugins(&view_id, plugins)
+            }
+            ClientNotification::ConfigChanged { view_id, changes } => {
+                self.handle_config_changed(&view_id, &changes)
+            }
+            ClientNotification::DefStyle(style) => self.styles.define(&style),
+            ClientNotification::LanguageChanged { view_id, language_id } => {
+                self.handle_language_changed(&view_id, language_id)
+            }
+            ClientNotification::ScrollTo { view_id, line, col } => {
+                self.handle_cursor_move(&view_id, line, col)
+            }
+            ClientNotification::Update { view_id, update } => {
+                self.handle_content_update(ctx, &view_id, &update)
+            }
+            ClientNotification::ThemeChanged { name, theme } => {
+                self.handle_theme_changed(name, &theme)
+            }
+            ClientNotification::Alert { msg } => self.handle_alert(ctx, msg),
+            ClientNotification::ShowHover { result, .. } => {
+                self.notify(ctx, Severity::Info, result)
+            }
+
+            // Sent by the InputController.
+            ClientNotification::SetPathForView { view_id, path } => {
+                self.set_path_for_view(&view_id, path)
+            }
+            ClientNotification::WriteToFile { view_id } => self.write_to_file(ctx, &view_id),
+            ClientNotification::CloseView { view_id } => self.close_view(ctx, &view_id),
+            ClientNotification::PushJump { view_id } => self.push_jump(&view_id),
+            ClientNotification::JumpBack { view_id } => self.jump_back(ctx, &view_id),
+            ClientNotification::JumpForward { view_id } => self.jump_forward(ctx, &view_id),
+            ClientNotification::MoveToFirstNonBlank { view_id } => {
+                self.move_to_first_non_blank(ctx, &view_id)
+            }
+            ClientNotification::MoveToMatchingBracket { view_id } => {
+                self.move_to_matching_bracket(ctx, &view_id)
+            }
+            ClientNotification::ToggleLineNumbers {} => self.toggle_line_numbers(),
+            ClientNotification::ToggleSoftWrap {} => self.toggle_soft_wrap(),
+            ClientNotification::ShowMessage { message, severity, sticky } => {
+                self.show_message(ctx, severity, message, sticky)
+            }
+            ClientNotification::ShowMessages {} => self.show_messages(ctx),
+            ClientNotification::HideMessages {} => self.hide_messages(ctx),
+            ClientNotification::UpdateCommandLine { command_line } => {
+                self.update_command_line(command_line)
+            }
+            ClientNotification::ListPlugins { view_id } => self.list_plugins(ctx, &view_id),
+            ClientNotification::ShowSetting { view_id, language, key } => {
+                self.show_setting(ctx, &view_id, language.as_deref(), &key)
+            }
+            ClientNotification::OpenPicker { view_id, kind } => self.open_picker(&view_id, kind),
+            ClientNotification::PickerMove { delta } => self.picker_move(ctx, delta),
+            ClientNotification::PickerSelect {} => self.picker_select(ctx),
+            ClientNotification::PickerCancel {} => self.picker_cancel(ctx),
+            ClientNotification::SelectTheme { theme } => self.select_theme(ctx, &theme),
+            ClientNotification::MakeSession { path } => self.make_session(ctx, path),
+            ClientNotification::RestoreView { view_id, line, col, first_line, first_col } => {
+                self.restore_view(ctx, &view_id, Cursor { line, col }, (first_line, first_col))
+            }
         };
+
+        self.fit_view(ctx);
+        self.render();
+    }
+
+    fn idle(&mut self, ctx: &xi_rpc::RpcCtx, token: usize) {
+        match token {
+            SWAP_TOKEN => self.write_swap_files(ctx),
+            MESSAGE_TOKEN if self.dismiss_message() => self.render(),
+            _ => (),
+        }
     }
 
     fn handle_request(&mut self, _ctx: &xi_rpc::RpcCtx, rpc: Self::Request) -> Result<serde_json::Value, xi_rpc::RemoteError> {
         info!("[request] {} -> {:#?}", rpc.method, rpc.params);
-        Ok(json!({}))
+
+        if !ClientRequest::is_known_method(&rpc.method) {
+            warn!("unknown request \"{}\" -> {}", &rpc.method, &rpc.params);
+            return Err(xi_rpc::RemoteError::custom(
+                METHOD_NOT_FOUND,
+                format!("unknown request {}", rpc.method),
+                None,
+            ));
+        }
+
+        let request = json!({ "method": &rpc.method, "params": &rpc.params });
+        match serde_json::from_value(request) {
+            Ok(ClientRequest::MeasureWidth(items)) => Ok(self.measure_width(&items)),
+            Ok(ClientRequest::Unknown) => unreachable!("the unknown methods are rejected above"),
+            Err(err) => {
+                error!("invalid request \"{}\" -> {}: {}", &rpc.method, &rpc.params, err);
+                Err(xi_rpc::RemoteError::InvalidRequest(Some(json!(err.to_string()))))
+            }
+        }
     }
 }
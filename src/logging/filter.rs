@@ -0,0 +1,144 @@
+use std::cmp::{self, Reverse};
+use std::fmt;
+use std::str::FromStr;
+
+use log::LevelFilter;
+
+/// The levels of the logs kept, parsed from directives like
+/// `info,xi_rpc=warn,xi_example_cli::events=trace`.
+///
+/// A directive without a module sets the default level, a module without a
+/// level keeps all its logs. A module also covers its submodules.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Filter {
+    default: LevelFilter,
+    /// The levels by module, the longest modules first so the most specific
+    /// directive applies.
+    modules: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    pub const fn new(default: LevelFilter) -> Self {
+        Filter {
+            default,
+            modules: Vec::new(),
+        }
+    }
+
+    pub fn enabled(&self, target: &str, level: log::Level) -> bool {
+        let level_filter = self
+            .modules
+            .iter()
+            .find(|(module, _)| match target.strip_prefix(module.as_str()) {
+                Some(rest) => rest.is_empty() || rest.starts_with("::"),
+                None => false,
+            })
+            .map_or(self.default, |&(_, level_filter)| level_filter);
+
+        level <= level_filter
+    }
+
+    /// Returns the most verbose level of the directives, the logs above it
+    /// are skipped without being formatted.
+    pub fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|&(_, level_filter)| level_filter)
+            .fold(self.default, cmp::max)
+    }
+}
+
+impl FromStr for Filter {
+    type Err = String;
+
+    fn from_str(directives: &str) -> Result<Self, Self::Err> {
+        let mut filter = Filter::new(LevelFilter::Info);
+
+        for directive in directives.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            let (module, level) = match directive.split_once('=') {
+                Some((module, level)) => (module.trim(), parse_level(level.trim())?),
+                None => match parse_level(directive) {
+                    Ok(level) => {
+                        filter.default = level;
+                        continue;
+                    }
+                    Err(_) => (directive, LevelFilter::Trace),
+                },
+            };
+
+            filter.modules.retain(|(other, _)| other != module);
+            filter.modules.push((module.to_string(), level));
+        }
+
+        filter.modules.sort_by_key(|(module, _)| Reverse(module.len()));
+        Ok(filter)
+    }
+}
+
+impl fmt::Display for Filter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.default.to_string().to_lowercase())?;
+        for (module, level) in &self.modules {
+            write!(f, ",{}={}", module, level.to_string().to_lowercase())?;
+        }
+        Ok(())
+    }
+}
+
+fn parse_level(level: &str) -> Result<LevelFilter, String> {
+    LevelFilter::from_str(level).map_err(|_| format!("invalid log level {}", level))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::Level;
+
+    fn filter(directives: &str) -> Filter {
+        directives.parse().unwrap()
+    }
+
+    #[test]
+    fn matches_the_modules_by_prefix() {
+        let filter = filter("warn,xi_rpc=trace");
+        assert!(filter.enabled("xi_rpc", Level::Trace));
+        assert!(filter.enabled("xi_rpc::peer", Level::Trace));
+        assert!(!filter.enabled("xi_rpcx", Level::Info));
+        assert!(!filter.enabled("xi_rpcx::peer", Level::Info));
+        assert!(filter.enabled("xi_rpcx", Level::Warn));
+    }
+
+    #[test]
+    fn applies_the_longest_module() {
+        let filter = filter("xi_core=trace,xi_core::plugins=error,xi_core::plugins::rpc=debug");
+        assert_eq!(filter.modules[0].0, "xi_core::plugins::rpc");
+        assert!(filter.enabled("xi_core::view", Level::Trace));
+        assert!(!filter.enabled("xi_core::plugins::manager", Level::Warn));
+        assert!(filter.enabled("xi_core::plugins::rpc", Level::Debug));
+        assert!(!filter.enabled("xi_core::plugins::rpc", Level::Trace));
+    }
+
+    #[test]
+    fn parses_the_bare_directives() {
+        let filter = filter("error, xi_rpc ,");
+        assert!(filter.enabled("xi_rpc", Level::Trace));
+        assert!(!filter.enabled("xi_core", Level::Warn));
+        assert_eq!(filter.max_level(), LevelFilter::Trace);
+        assert_eq!(filter.to_string(), "error,xi_rpc=trace");
+    }
+
+    #[test]
+    fn keeps_the_last_directive_of_a_module() {
+        let filter = filter("xi_rpc=trace,xi_rpc=warn");
+        assert_eq!(filter.to_string(), "info,xi_rpc=warn");
+        assert_eq!(filter.max_level(), LevelFilter::Info);
+    }
+
+    #[test]
+    fn rejects_the_invalid_levels() {
+        assert_eq!(
+            "xi_rpc=loud".parse::<Filter>(),
+            Err(String::from("invalid log level loud"))
+        );
+    }
+}
@@ -1,19 +1,35 @@
 use std::fs;
-use std::io;
+use std::io::{self, Write};
 use std::path::Path;
+use std::sync::RwLock;
+
+use log::LevelFilter;
+
+mod filter;
+mod rotation;
+
+pub use self::filter::Filter;
+use self::rotation::RotatingFile;
+
+/// The size over which the log file is rotated.
+const MAX_LOG_SIZE: u64 = 4 * 1024 * 1024;
+/// The number of rotated log files kept.
+const LOG_GENERATIONS: usize = 3;
+
+/// The filter of the logs, changed at runtime by `:log`.
+static FILTER: RwLock<Filter> = RwLock::new(Filter::new(LevelFilter::Info));
 
 pub fn setup(logging_path: &Path) -> Result<(), fern::InitError> {
-    let level_filter = match std::env::var("XI_LOG") {
-        Ok(level) => match level.to_lowercase().as_ref() {
-            "trace" => log::LevelFilter::Trace,
-            "debug" => log::LevelFilter::Debug,
-            _ => log::LevelFilter::Info,
-        },
-        // Default to info
-        Err(_) => log::LevelFilter::Info,
+    // `XI_LOG` takes directives like `debug,xi_rpc=warn`, the info level is
+    // used by default.
+    let directives = std::env::var("XI_LOG").unwrap_or_default();
+    let (filter, invalid) = match directives.parse::<Filter>() {
+        Ok(filter) => (filter, None),
+        Err(err) => (Filter::new(LevelFilter::Info), Some(err)),
     };
 
     create_log_directory(logging_path)?;
+    let log_file = RotatingFile::open(logging_path, MAX_LOG_SIZE, LOG_GENERATIONS)?;
 
     let fern_dispatch = fern::Dispatch::new()
         .format(|out, message, record| {
@@ -25,17 +41,31 @@ pub fn setup(logging_path: &Path) -> Result<(), fern::InitError> {
                 message,
             ))
         })
-        .level(level_filter)
-        .chain(fern::log_file(logging_path)?);
+        .filter(|metadata| FILTER.read().unwrap().enabled(metadata.target(), metadata.level()))
+        .chain(Box::new(log_file) as Box<dyn Write + Send>);
 
     // Start fern
     fern_dispatch.apply()?;
-    info!("Logging with fern is set up to level {}", level_filter);
+    set_filter(filter);
+    if let Some(err) = invalid {
+        warn!("ignoring XI_LOG={}: {}", directives, err);
+    }
+    info!("Logging with fern is set up to level {}", self::filter());
     info!("Writing logs to: {}", logging_path.display());
 
     Ok(())
 }
 
+/// Replaces the filter of the logs.
+pub fn set_filter(filter: Filter) {
+    log::set_max_level(filter.max_level());
+    *FILTER.write().unwrap() = filter;
+}
+
+pub fn filter() -> Filter {
+    FILTER.read().unwrap().clone()
+}
+
 /// This function tries to create the parent directories for a file
 ///
 /// It wraps around the `parent()` function of `Path` which returns an `Option<&Path>` and
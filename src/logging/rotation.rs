@@ -0,0 +1,140 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+/// A log file renamed once it grows over `max_size`, like
+/// `xi-example-log.txt.1`. The older generations are shifted, and the ones
+/// over `generations` are removed.
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size: u64,
+    generations: usize,
+    file: File,
+    size: u64,
+}
+
+impl RotatingFile {
+    pub fn open(path: &Path, max_size: u64, generations: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(RotatingFile {
+            path: path.to_path_buf(),
+            max_size,
+            generations,
+            file,
+            size,
+        })
+    }
+
+    fn generation_path(&self, generation: usize) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(format!(".{}", generation));
+        PathBuf::from(path)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.generations == 0 {
+            self.file.set_len(0)?;
+            self.size = 0;
+            return Ok(());
+        }
+
+        for generation in (1..self.generations).rev() {
+            let from = self.generation_path(generation);
+            if from.exists() {
+                fs::rename(from, self.generation_path(generation + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.generation_path(1))?;
+
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    /// Flushed after each record by fern: the file is rotated there so a
+    /// record is never split between two files.
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+        if self.size >= self.max_size {
+            self.rotate()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    fn log_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("xi-example-log-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn log(file: &mut RotatingFile, record: &str) {
+        file.write_all(record.as_bytes()).unwrap();
+        file.flush().unwrap();
+    }
+
+    #[test]
+    fn shifts_the_generations() {
+        let dir = log_dir("shift");
+        let path = dir.join("log.txt");
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        let mut file = RotatingFile::open(&path, 4, 2).unwrap();
+
+        log(&mut file, "ab\n");
+        assert_eq!(read("log.txt"), "ab\n");
+        log(&mut file, "cd\n");
+        assert_eq!(read("log.txt"), "");
+        assert_eq!(read("log.txt.1"), "ab\ncd\n");
+
+        log(&mut file, "ef\ngh\n");
+        assert_eq!(read("log.txt.1"), "ef\ngh\n");
+        assert_eq!(read("log.txt.2"), "ab\ncd\n");
+
+        // The oldest generation is dropped.
+        log(&mut file, "ij\nkl\n");
+        assert_eq!(read("log.txt.1"), "ij\nkl\n");
+        assert_eq!(read("log.txt.2"), "ef\ngh\n");
+        assert!(!dir.join("log.txt.3").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn appends_to_the_existing_file() {
+        let dir = log_dir("append");
+        let path = dir.join("log.txt");
+        fs::write(&path, "ab\n").unwrap();
+
+        let mut file = RotatingFile::open(&path, 4, 1).unwrap();
+        log(&mut file, "cd\n");
+        assert_eq!(fs::read_to_string(dir.join("log.txt.1")).unwrap(), "ab\ncd\n");
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn truncates_without_generations() {
+        let dir = log_dir("truncate");
+        let path = dir.join("log.txt");
+        let mut file = RotatingFile::open(&path, 4, 0).unwrap();
+
+        log(&mut file, "ab\ncd\n");
+        log(&mut file, "ef\n");
+        assert_eq!(fs::read_to_string(&path).unwrap(), "ef\n");
+        assert!(!dir.join("log.txt.1").exists());
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -6,34 +6,93 @@ extern crate chrono;
 extern crate log;
 extern crate fern;
 
+#[macro_use]
+extern crate serde_derive;
 #[macro_use]
 extern crate serde_json;
 
 //use std::cell::RefCell;
 //use std::fs::File;
 //use std::io::prelude::*;
-//use std::process::exit;
+use std::path::PathBuf;
+use std::process::exit;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc};
 //use std::rc::Rc;
 use std::thread;
 
+
 use xi_rpc::{Peer, RpcLoop};
 
+mod channel;
+mod config;
 mod core;
+mod crash;
+mod error;
 mod logging;
 mod events;
+mod protocol;
+mod server;
+mod session;
+mod swap;
+mod trace;
 mod ui;
 
+#[cfg(test)]
+mod tests;
+
+use config::Config;
+use protocol::CoreNotification;
 use ui::keyboard::TermionKeyboard;
+use ui::terminal::TermionTerminal;
 use ui::InputController;
 
 static CONFIG_DIR :  &'static str = "/localhome/sean.wilson/.config/xi-example";
 
 fn setup_config(core: &dyn Peer) {
 
-  core.send_rpc_notification(
-    "client_started",
-    &json!({ "config_dir": CONFIG_DIR, }),
-    );
+  CoreNotification::ClientStarted {
+    config_dir: Some(CONFIG_DIR.to_string()),
+    client_extras_dir: None,
+  }
+  .send(core);
+}
+
+fn load_config() -> Config {
+    let config_path = CONFIG_DIR.to_owned() + "/xi-example-config.json";
+    match Config::load(std::path::Path::new(&config_path)) {
+        Ok(config) => config,
+        Err(err) => {
+            error!("failed to load the config from {}: {}", config_path, err);
+            Config::default()
+        }
+    }
+}
+
+/// The external xi-core binary to use, from `$XI_CORE` or the config. The
+/// embedded xi-core is used if none is set.
+fn xi_core_path(config: &Config) -> Option<PathBuf> {
+    match std::env::var_os("XI_CORE") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => config.xi_core.clone(),
+    }
+}
+
+/// Prints the screen rendered by replaying the trace `trace_path` on a screen
+/// of `size`, given as `<columns>x<rows>`.
+fn replay(trace_path: &str, size: Option<&str>) -> Result<(), String> {
+  let size = size.unwrap_or("80x24");
+  let (columns, rows) = size
+    .split_once('x')
+    .and_then(|(columns, rows)| Some((columns.parse().ok()?, rows.parse().ok()?)))
+    .ok_or_else(|| format!("invalid screen size: {}", size))?;
+
+  let terminal = trace::replay(std::path::Path::new(trace_path), load_config(), columns, rows)
+    .map_err(|err| format!("failed to replay {}: {}", trace_path, err))?;
+  for row in terminal.rows() {
+    println!("{}", row);
+  }
+  Ok(())
 }
 
 fn setup_logger() {
@@ -44,42 +103,173 @@ fn setup_logger() {
 fn main() {
 
   setup_logger();
+  crash::install(PathBuf::from(CONFIG_DIR));
+
+  let mut args: Vec<String> = std::env::args().skip(1).collect();
+
+  // `--record <trace>` records the messages exchanged with xi-core.
+  let mut recorder = None;
+  if let Some(idx) = args.iter().position(|arg| arg == "--record") {
+    if idx + 1 >= args.len() {
+      println!("--record expects the path of the trace");
+      exit(1);
+    }
+    let trace_path: Vec<String> = args.drain(idx..idx + 2).skip(1).collect();
+    match trace::Recorder::create(std::path::Path::new(&trace_path[0])) {
+      Ok(trace) => recorder = Some(trace),
+      Err(err) => {
+        println!("failed to create {}: {}", trace_path[0], err);
+        exit(1);
+      }
+    }
+  }
+
+  // The views open on exit are saved to the session file, `--session
+  // [<file>]` reopens them.
+  let session_file = PathBuf::from(CONFIG_DIR).join("session.json");
+
+  // `--server <socket>` runs a session server, `--attach <socket>` attaches
+  // the terminal to it, `--replay <trace> [<columns>x<rows>]` prints the
+  // screen rendered from a trace.
+  let (socket_path, session_path, file_path) = match args.as_slice() {
+    [flag, socket_path] if flag == "--server" => {
+      if let Err(err) = server::run(std::path::Path::new(socket_path)) {
+        println!("failed to run the session server: {}", err);
+        exit(1);
+      }
+      return;
+    }
+    [flag, trace_path, size @ ..] if flag == "--replay" => {
+      if let Err(err) = replay(trace_path, size.first().map(String::as_str)) {
+        println!("{}", err);
+        exit(1);
+      }
+      return;
+    }
+    [flag, socket_path, rest @ ..] if flag == "--attach" => {
+      (Some(PathBuf::from(socket_path)), None, rest.first().cloned())
+    }
+    [flag, rest @ ..] if flag == "--session" => {
+      let session_path = rest.first().map_or_else(|| session_file.clone(), PathBuf::from);
+      (None, Some(session_path), None)
+    }
+    [file_path, ..] => (None, None, Some(file_path.clone())),
+    [] => (None, None, None),
+  };
+  let file_path = file_path.unwrap_or_else(|| String::from("/tmp/xi-example.txt"));
 
-  let (client_to_core_writer, core_to_client_reader, client_to_client_writer) =
-    core::start_xi_core();
+  let config = load_config();
+
+  let core = match (&socket_path, xi_core_path(&config)) {
+    (Some(socket_path), _) => core::connect_xi_core(socket_path),
+    (None, Some(path)) => core::spawn_xi_core(&path),
+    (None, None) => Ok(core::start_xi_core()),
+  };
+  let (
+    mut client_to_core_writer,
+    mut core_to_client_reader,
+    client_to_client_writer,
+    core_thread,
+  ) = match core {
+    Ok(core) => core,
+    Err(err) => {
+      println!("failed to start xi-core: {}", err);
+      exit(1);
+    }
+  };
+
+  client_to_core_writer.add_tap(crash::tap(trace::Direction::ToCore));
+  core_to_client_reader.add_tap(crash::tap(trace::Direction::ToFrontend));
+  if let Some(ref recorder) = recorder {
+    client_to_core_writer.add_tap(recorder.tap(trace::Direction::ToCore));
+    core_to_client_reader.add_tap(recorder.tap(trace::Direction::ToFrontend));
+  }
 
   let mut front_event_loop = RpcLoop::new(client_to_core_writer);
 
   let raw_peer = front_event_loop.get_raw_peer();
   setup_config(&raw_peer);
 
+  // The terminal is put in raw mode as soon as possible: the keystrokes are
+  // read one by one, the terminal must not wait for a new line nor echo them.
+  let terminal = TermionTerminal::new().expect("failed to initialize the terminal");
+
+  // Set once the user detached from the session, the views are left open.
+  let detached = Arc::new(AtomicBool::new(false));
+  let event_detached = detached.clone();
+
+  let swap_dir = swap::SwapDir::new(PathBuf::from(CONFIG_DIR).join("swap"));
+  let event_swap_dir = swap_dir.clone();
+  let (themes_sender, themes_receiver) = mpsc::channel();
+
   let child = thread::spawn(move || {
 
-    let mut event_handler = events::EventController::new();
+    let mut event_handler = events::EventController::new(config, Box::new(terminal));
+    event_handler.use_swap_dir(event_swap_dir);
+    event_handler.use_session_file(session_file);
+    event_handler.send_themes_to(themes_sender);
     front_event_loop
       .mainloop(|| core_to_client_reader, &mut event_handler)
       .unwrap();
-  });
 
+    event_handler.save_session();
+
+    // The pending saves were sent to xi-core before the loop stopped, they
+    // are handled before the views are closed.
+    if !event_detached.load(Ordering::SeqCst) {
+      event_handler.close_views(&front_event_loop.get_raw_peer());
+    }
+  });
 
-  // TODO Initialize UI
 
   // Create a reader from the std input stream.
-  let mut keyboard_device = TermionKeyboard::from_reader(std::io::stdin());
+  let keyboard_device = TermionKeyboard::from_reader(std::io::stdin());
 
   // Create a controller which will be used to map inputs from the keyboard
   // to actions for xi
-  //let mut input_controller = InputController::new(
-  //  keyboard_device, 
-  //  client_to_client_writer);
+  let mut input_controller = InputController::new(
+    Box::new(keyboard_device),
+    client_to_client_writer);
+
+  input_controller.use_swap_dir(swap_dir);
+  input_controller.receive_themes_from(themes_receiver);
+
+  let macros_path = CONFIG_DIR.to_owned() + "/macros.json";
+  if let Err(err) = input_controller.persist_macros(std::path::Path::new(&macros_path)) {
+      error!("failed to load the macros from {}: {}", macros_path, err);
+  }
+
+  let opened = match (socket_path, session_path) {
+    (Some(_), _) => input_controller.attach(&raw_peer, &file_path),
+    (None, Some(session_path)) => {
+      input_controller.restore_session(&raw_peer, &session_path, &file_path)
+    }
+    (None, None) => input_controller.open_file(&raw_peer, &file_path),
+  };
 
   // Begin the keyboard controller's event loop. We will now 
   // start processing input events from the keyboard
-  //if let Err(err) = input_controller.start_keyboard_event_loop(&raw_peer) {
-  //    println!("an error occured: {}", err);
-  //    exit(1);
-  //}
+  let res = opened.and_then(|()| input_controller.start_keyboard_event_loop(&raw_peer));
+
+  // The EventController stops first, the terminal is restored when it is
+  // dropped. xi-core stops once it closed the views, unless the user
+  // detached from the session.
+  detached.store(input_controller.detached(), Ordering::SeqCst);
+  input_controller.shutdown();
+  let mut status = 0;
+  if child.join().is_err() {
+      error!("the EventController panicked");
+      status = 1;
+  }
+  core_thread.shutdown();
+
+  // Printed once the terminal is restored.
+  if let Err(err) = res {
+      error!("an error occured: {}", err);
+      println!("{}", err);
+      status = 1;
+  }
 
-  child.join().unwrap();
+  exit(status);
 
 }
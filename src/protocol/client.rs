@@ -0,0 +1,346 @@
+use serde_json::Value;
+
+use super::split;
+use crate::core::ClientToClientWriter;
+use crate::error::Result;
+use crate::ui::messages::Severity;
+use crate::ui::picker::PickerKind;
+use crate::ui::status_bar::Alignment;
+
+/// The notifications handled by the EventController.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum ClientNotification {
+    Update {
+        view_id: String,
+        update: Update,
+    },
+    ScrollTo {
+        view_id: String,
+        line: usize,
+        col: usize,
+    },
+    DefStyle(StyleDef),
+    AvailableLanguages {
+        languages: Vec<String>,
+    },
+    LanguageChanged {
+        view_id: String,
+        language_id: String,
+    },
+    AvailableThemes {
+        themes: Vec<String>,
+    },
+    ThemeChanged {
+        name: String,
+        theme: Theme,
+    },
+    AvailablePlugins {
+        view_id: String,
+        plugins: Vec<PluginInfo>,
+    },
+    PluginStarted {
+        view_id: String,
+        plugin: String,
+    },
+    PluginStopped {
+        view_id: String,
+        plugin: String,
+        /// 0 when the plugin was stopped on request.
+        #[serde(default)]
+        code: i64,
+    },
+    /// The changed settings of a view, parsed by `Settings`.
+    ConfigChanged {
+        view_id: String,
+        changes: Value,
+    },
+    /// A message of xi-core for the user, like an error of a plugin.
+    Alert {
+        msg: String,
+    },
+    /// The hover definition requested to a plugin, usually in markdown.
+    ShowHover {
+        view_id: String,
+        request_id: usize,
+        result: String,
+    },
+    // Sent by xi-core for the features of the other frontends, ignored.
+    UpdateCmds {
+        view_id: String,
+    },
+    FindStatus {
+        view_id: String,
+    },
+    ReplaceStatus {
+        view_id: String,
+    },
+    AddStatusItem(StatusItem),
+    UpdateStatusItem {
+        key: String,
+        value: String,
+    },
+    RemoveStatusItem {
+        key: String,
+    },
+
+    // Sent by the InputController.
+    SetPathForView {
+        view_id: String,
+        path: Option<String>,
+    },
+    WriteToFile {
+        view_id: String,
+    },
+    /// Closes a view shown for a while, like the changes of a swap file.
+    CloseView {
+        view_id: String,
+    },
+    PushJump {
+        view_id: String,
+    },
+    JumpBack {
+        view_id: String,
+    },
+    JumpForward {
+        view_id: String,
+    },
+    MoveToFirstNonBlank {
+        view_id: String,
+    },
+    MoveToMatchingBracket {
+        view_id: String,
+    },
+    ToggleLineNumbers {},
+    ToggleSoftWrap {},
+    ShowMessage {
+        message: String,
+        #[serde(default)]
+        severity: Severity,
+        /// Kept on the status bar until replaced, like a prompt waiting for
+        /// a key. The other messages are dismissed after a while.
+        #[serde(default)]
+        sticky: bool,
+    },
+    /// Lists the messages shown so far over the current view, until
+    /// `HideMessages`.
+    ShowMessages {},
+    HideMessages {},
+    /// The text of the command line, `None` once the edition is over.
+    UpdateCommandLine {
+        command_line: Option<String>,
+    },
+    ListPlugins {
+        view_id: String,
+    },
+    /// Shows a setting of the view, or of the views using `language` if
+    /// given.
+    ShowSetting {
+        view_id: String,
+        #[serde(default)]
+        language: Option<String>,
+        key: String,
+    },
+    OpenPicker {
+        view_id: String,
+        kind: PickerKind,
+    },
+    PickerMove {
+        delta: i64,
+    },
+    PickerSelect {},
+    PickerCancel {},
+    SelectTheme {
+        theme: String,
+    },
+    /// Saves the session to `path`, or to the session file if `None`.
+    MakeSession {
+        path: Option<String>,
+    },
+    /// Moves the cursor of a view reopened from a session back to `line` and
+    /// `col`, `first_line` and `first_col` being displayed first.
+    RestoreView {
+        view_id: String,
+        line: usize,
+        col: usize,
+        first_line: usize,
+        first_col: usize,
+    },
+
+    /// Any other method, only parsed without params by `is_known_method`.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ClientNotification {
+    /// Returns true if `method` is one of the notifications, whatever its
+    /// params.
+    pub fn is_known_method(method: &str) -> bool {
+        let message = json!({ "method": method });
+        !matches!(serde_json::from_value(message), Ok(ClientNotification::Unknown))
+    }
+
+    pub fn send(&self, front_event_writer: &mut ClientToClientWriter) -> Result<()> {
+        let (method, params) = split(self);
+        front_event_writer.send_rpc_notification(&method, &params)
+    }
+}
+
+/// The requests sent by xi-core to the frontend.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum ClientRequest {
+    MeasureWidth(Vec<MeasureWidthItem>),
+
+    /// Any other method, only parsed without params by `is_known_method`.
+    #[serde(other)]
+    Unknown,
+}
+
+impl ClientRequest {
+    /// Returns true if `method` is one of the requests, whatever its params.
+    pub fn is_known_method(method: &str) -> bool {
+        let message = json!({ "method": method });
+        !matches!(serde_json::from_value(message), Ok(ClientRequest::Unknown))
+    }
+}
+
+/// An item of the `measure_width` requests, the strings being displayed
+/// with the style `id`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MeasureWidthItem {
+    pub id: u64,
+    pub strings: Vec<String>,
+}
+
+/// The `update` param of the `update` notifications.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Update {
+    pub ops: Vec<UpdateOp>,
+    #[serde(default)]
+    pub annotations: Vec<Annotation>,
+    /// False while the buffer has unsaved changes.
+    #[serde(default)]
+    pub pristine: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UpdateOp {
+    pub op: UpdateOpKind,
+    pub n: usize,
+    /// For `copy`, the new number of the first copied line.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ln: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<Line>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateOpKind {
+    /// Keeps the next lines of the old cache.
+    Copy,
+    /// Drops the next lines of the old cache.
+    Skip,
+    /// Adds lines which are not known yet.
+    Invalidate,
+    /// Adds new lines.
+    Ins,
+    /// Keeps the next lines of the old cache with new cursors and styles.
+    Update,
+}
+
+/// A line of text as sent by xi-core in the `update` notifications.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Line {
+    #[serde(default)]
+    pub text: String,
+    /// Byte offsets of the cursors in the line.
+    #[serde(default)]
+    pub cursor: Vec<usize>,
+    /// The line number, only present for the first visual line of a
+    /// logical line.
+    pub ln: Option<usize>,
+    /// The style spans as `[start, length, style_id]` triples, each start
+    /// being relative to the end of the previous span.
+    #[serde(default)]
+    pub styles: Vec<i64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    #[serde(rename = "type")]
+    pub kind: String,
+    /// The regions as `[start_line, start_col, end_line, end_col]`.
+    pub ranges: Vec<[usize; 4]>,
+}
+
+/// The params of the `def_style` notifications.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StyleDef {
+    pub id: u64,
+    /// The colors as ARGB values.
+    pub fg_color: Option<u32>,
+    pub bg_color: Option<u32>,
+    pub weight: Option<u64>,
+    #[serde(default)]
+    pub italic: bool,
+    #[serde(default)]
+    pub underline: bool,
+}
+
+/// The colors of a theme, only the ones used by a terminal are kept.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Theme {
+    pub foreground: Option<Rgba>,
+    pub background: Option<Rgba>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct Rgba {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+    pub a: u8,
+}
+
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct PluginInfo {
+    pub name: String,
+    #[serde(default)]
+    pub running: bool,
+}
+
+/// The params of the `add_status_item` notifications.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct StatusItem {
+    pub key: String,
+    pub value: String,
+    #[serde(default)]
+    pub alignment: Alignment,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn knows_the_methods() {
+        assert!(ClientNotification::is_known_method("update"));
+        assert!(ClientNotification::is_known_method("toggle_line_numbers"));
+        assert!(!ClientNotification::is_known_method("unknown"));
+        assert!(!ClientNotification::is_known_method("find_next"));
+        assert!(ClientRequest::is_known_method("measure_width"));
+        assert!(!ClientRequest::is_known_method("update"));
+    }
+
+    #[test]
+    fn rejects_the_unknown_variants_of_the_params() {
+        let notification = json!({
+            "method": "update",
+            "params": { "view_id": "view-id-1", "update": { "ops": [{ "op": "move", "n": 1 }] } },
+        });
+        assert!(serde_json::from_value::<ClientNotification>(notification).is_err());
+        assert!(ClientNotification::is_known_method("update"));
+    }
+}
@@ -0,0 +1,197 @@
+use serde_json::Value;
+use xi_rpc::Peer;
+
+use super::split;
+
+/// The notifications sent to xi-core.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum CoreNotification {
+    ClientStarted {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        config_dir: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        client_extras_dir: Option<String>,
+    },
+    Edit(Edit),
+    Save {
+        view_id: String,
+        file_path: String,
+    },
+    CloseView {
+        view_id: String,
+    },
+    SetTheme {
+        theme_name: String,
+    },
+    SetLanguage {
+        view_id: String,
+        language_id: String,
+    },
+    ModifyUserConfig {
+        domain: ConfigDomain,
+        changes: Value,
+    },
+    Plugin(PluginCommand),
+}
+
+impl CoreNotification {
+    pub fn edit(view_id: &str, method: EditMethod) -> Self {
+        CoreNotification::Edit(Edit {
+            view_id: view_id.to_string(),
+            method,
+        })
+    }
+
+    pub fn send(&self, core: &dyn Peer) {
+        let (method, params) = split(self);
+        core.send_rpc_notification(&method, &params);
+    }
+}
+
+/// The requests sent to xi-core.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum CoreRequest {
+    NewView {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        file_path: Option<String>,
+    },
+    Edit(Edit),
+    /// Answered by the session server rather than xi-core, with the views
+    /// kept open by the session.
+    Attach {},
+}
+
+impl CoreRequest {
+    pub fn edit(view_id: &str, method: EditMethod) -> Self {
+        CoreRequest::Edit(Edit {
+            view_id: view_id.to_string(),
+            method,
+        })
+    }
+
+    pub fn send(&self, core: &dyn Peer) -> Result<Value, xi_rpc::Error> {
+        let (method, params) = split(self);
+        core.send_rpc_request(&method, &params)
+    }
+}
+
+/// A view kept open by the session server, as listed in the `attach`
+/// responses.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SessionView {
+    pub view_id: String,
+    pub file_path: Option<String>,
+}
+
+/// The params of the `edit` notifications and requests.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Edit {
+    pub view_id: String,
+    #[serde(flatten)]
+    pub method: EditMethod,
+}
+
+/// The methods of the `edit` notifications and requests, applied to a view.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "snake_case")]
+pub enum EditMethod {
+    Insert {
+        chars: String,
+    },
+    InsertNewline,
+    Paste {
+        chars: String,
+    },
+    Copy,
+    Cut,
+    DeleteBackward,
+    DeleteForward,
+
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    MoveWordLeft,
+    MoveWordRight,
+    MoveToBeginningOfDocument,
+    MoveToEndOfDocument,
+    MoveToLeftEndOfLine,
+    MoveToRightEndOfLine,
+    ScrollPageUp,
+    ScrollPageDown,
+
+    MoveUpAndModifySelection,
+    MoveDownAndModifySelection,
+    MoveLeftAndModifySelection,
+    MoveRightAndModifySelection,
+    MoveWordLeftAndModifySelection,
+    MoveWordRightAndModifySelection,
+    MoveToBeginningOfDocumentAndModifySelection,
+    MoveToEndOfDocumentAndModifySelection,
+    MoveToLeftEndOfLineAndModifySelection,
+    MoveToRightEndOfLineAndModifySelection,
+
+    /// Moves the cursor to a line, numbered from 0.
+    GotoLine {
+        line: usize,
+    },
+    Gesture {
+        line: usize,
+        col: usize,
+        ty: GestureType,
+    },
+    /// Asks for the lines of the `[first, last)` region.
+    Scroll(usize, usize),
+    /// Asks for the lines of the `[first, last)` region once, without
+    /// scrolling.
+    RequestLines(usize, usize),
+
+    AddSelectionAbove,
+    AddSelectionBelow,
+    SelectionForFind {
+        case_sensitive: bool,
+    },
+    FindNext {
+        wrap_around: bool,
+        allow_same: bool,
+        modify_selection: SelectionModifier,
+    },
+    SelectAll,
+    SelectionIntoLines,
+    CollapseSelections,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GestureType {
+    PointSelect,
+}
+
+/// How a search changes the selections.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SelectionModifier {
+    /// Adds the match to the selections.
+    Add,
+}
+
+/// The settings changed by a `modify_user_config`.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigDomain {
+    General,
+    /// The views using a language.
+    Syntax(String),
+    /// A single view.
+    UserOverride(String),
+}
+
+/// The params of the `plugin` notifications.
+#[derive(Debug, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum PluginCommand {
+    Start { view_id: String, plugin_name: String },
+    Stop { view_id: String, plugin_name: String },
+}
@@ -0,0 +1,36 @@
+//! The messages of the xi protocol used by the frontend, and the ones sent
+//! by the InputController to the EventController.
+//!
+//! Each enum is serialized as `{"method": ..., "params": ...}`, the shape of
+//! the JSON-RPC messages.
+
+mod client;
+mod core;
+
+use serde::Serialize;
+use serde_json::Value;
+
+pub use self::client::{
+    ClientNotification, ClientRequest, Line, MeasureWidthItem, PluginInfo, Rgba, StatusItem,
+    StyleDef, Theme, Update, UpdateOpKind,
+};
+pub use self::core::{
+    ConfigDomain, CoreNotification, CoreRequest, EditMethod, GestureType, PluginCommand,
+    SelectionModifier, SessionView,
+};
+
+/// Splits a message into its method and params, as expected by `Peer`.
+fn split<T: Serialize>(message: &T) -> (String, Value) {
+    match serde_json::to_value(message) {
+        Ok(Value::Object(mut message)) => {
+            let method = match message.remove("method") {
+                Some(Value::String(method)) => method,
+                _ => unreachable!("the messages are serialized with their method"),
+            };
+            let params = message.remove("params").unwrap_or_else(|| json!({}));
+            (method, params)
+        }
+        Ok(message) => unreachable!("the messages are serialized as objects: {}", message),
+        Err(err) => unreachable!("the messages can always be serialized: {}", err),
+    }
+}
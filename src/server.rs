@@ -0,0 +1,484 @@
+//! A session server keeping xi-core and its views alive between the
+//! frontends attached to it, like a tmux session.
+//!
+//! The messages are forwarded as they are between xi-core and the attached
+//! frontend. The server only looks at them to know the open views and to
+//! replay the state xi-core sends once, like the themes, to the frontends
+//! attaching later.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::Shutdown;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use serde_json::Value;
+use xi_rpc::{RawPeer, RpcLoop};
+
+use crate::channel::{Reader, Writer};
+use crate::core;
+use crate::protocol::{CoreNotification, SessionView};
+
+/// The error code answering the requests of xi-core while no frontend is
+/// attached.
+const NO_FRONTEND: i64 = -32000;
+
+/// The notifications of xi-core setting the state of a view, replayed until
+/// it is closed.
+const VIEW_NOTIFICATIONS: &[&str] = &["available_plugins", "language_changed", "config_changed"];
+
+/// The state of the session, shared by the threads of the server.
+#[derive(Default)]
+struct Session {
+    /// The attached frontend with its number, the connections being
+    /// numbered from 1.
+    client: Option<(usize, UnixStream)>,
+    connections: usize,
+    views: Vec<SessionView>,
+    /// The requests of the frontends waiting for the response of xi-core,
+    /// by the id given by the server: the frontends all number their
+    /// requests from 0. Each one is kept with its connection and its own
+    /// id.
+    requests: HashMap<u64, (usize, Value)>,
+    next_id: u64,
+    /// The `new_view` requests waiting for their response, by the id given
+    /// by the server.
+    pending_views: HashMap<u64, Option<String>>,
+    /// The notifications replayed to the frontends attaching, with the key
+    /// of the state they set, like `def_style:3`. A notification replaces
+    /// the previous one with the same key.
+    replay: Vec<(String, Value)>,
+    theme: Option<String>,
+    stopping: bool,
+}
+
+impl Session {
+    /// Sends `message` to the attached frontend, returns false if there is
+    /// none. The frontend is detached if the message can't be sent.
+    fn send(&mut self, message: &str) -> bool {
+        let stream = match self.client {
+            Some((_, ref mut stream)) => stream,
+            None => return false,
+        };
+
+        if write_line(stream, message).is_err() {
+            warn!("failed to send to the frontend, it is detached");
+            self.client = None;
+            return false;
+        }
+        true
+    }
+
+    /// Tracks and forwards a message sent by xi-core. Returns the error
+    /// answering it if it is a request and no frontend is attached to answer
+    /// it.
+    fn handle_core_message(&mut self, message: &str) -> Option<String> {
+        let parsed: Value = match serde_json::from_str(message) {
+            Ok(parsed) => parsed,
+            Err(_) => {
+                self.send(message);
+                return None;
+            }
+        };
+
+        match (parsed.get("id"), parsed["method"].is_string()) {
+            (Some(_), false) => self.forward_response(parsed),
+            (Some(id), true) => {
+                if !self.send(message) {
+                    warn!("no frontend to answer the request {}", message.trim_end());
+                    let error = json!({
+                        "id": id,
+                        "error": { "code": NO_FRONTEND, "message": "no frontend attached" },
+                    });
+                    return Some(error.to_string());
+                }
+            }
+            (None, _) => {
+                self.observe_core_notification(&parsed);
+                self.send(message);
+            }
+        }
+        None
+    }
+
+    /// Sends the response of xi-core to the frontend which sent the request,
+    /// with its own id. It is dropped if the frontend was detached since.
+    fn forward_response(&mut self, mut response: Value) {
+        let id = match response["id"].as_u64() {
+            Some(id) => id,
+            None => return,
+        };
+        let (connection, client_id) = match self.requests.remove(&id) {
+            Some(request) => request,
+            None => return,
+        };
+
+        if let Some(file_path) = self.pending_views.remove(&id) {
+            if let Some(view_id) = response["result"].as_str() {
+                self.views.push(SessionView {
+                    view_id: view_id.to_string(),
+                    file_path,
+                });
+            }
+        }
+
+        if !matches!(self.client, Some((attached, _)) if attached == connection) {
+            debug!("dropped the response to the detached frontend {}", connection);
+            return;
+        }
+        response["id"] = client_id;
+        self.send(&response.to_string());
+    }
+
+    /// Tracks the state changed by a notification sent by xi-core.
+    fn observe_core_notification(&mut self, message: &Value) {
+        let method = match message["method"].as_str() {
+            Some(method) => method,
+            None => return,
+        };
+        let params = &message["params"];
+        let key = match method {
+            "available_themes" | "available_languages" | "theme_changed" => method.to_string(),
+            _ if VIEW_NOTIFICATIONS.contains(&method) => match params["view_id"].as_str() {
+                Some(view_id) => view_key(method, view_id),
+                None => return,
+            },
+            "def_style" => format!("{}:{}", method, params["id"]),
+            _ => return,
+        };
+
+        if method == "theme_changed" {
+            self.theme = params["name"].as_str().map(String::from);
+        }
+
+        let mut message = message.clone();
+        if let Some(idx) = self.replay.iter().position(|(replayed, _)| *replayed == key) {
+            let (_, previous) = self.replay.remove(idx);
+            // Only the settings which changed are sent, the previous ones
+            // are kept.
+            let previous = previous["params"]["changes"].as_object();
+            if let ("config_changed", Some(previous), Some(new)) =
+                (method, previous, params["changes"].as_object())
+            {
+                let mut changes = previous.clone();
+                changes.extend(new.clone());
+                message["params"]["changes"] = Value::Object(changes);
+            }
+        }
+        self.replay.push((key, message));
+    }
+
+    /// Tracks the views opened and closed by the frontend `connection`. The
+    /// id of its requests is replaced by one unique to the session.
+    fn observe_client_message(&mut self, connection: usize, message: &mut Value) {
+        if let (Some(client_id), true) = (message.get("id"), message["method"].is_string()) {
+            let id = self.next_id;
+            self.next_id += 1;
+            self.requests.insert(id, (connection, client_id.clone()));
+            message["id"] = json!(id);
+        }
+
+        match message["method"].as_str() {
+            Some("new_view") => {
+                if let Some(id) = message["id"].as_u64() {
+                    let file_path = message["params"]["file_path"].as_str().map(String::from);
+                    self.pending_views.insert(id, file_path);
+                }
+            }
+            Some("close_view") => {
+                let view_id = message["params"]["view_id"].as_str().unwrap_or_default();
+                self.views.retain(|view| view.view_id != view_id);
+                let keys: Vec<String> =
+                    VIEW_NOTIFICATIONS.iter().map(|method| view_key(method, view_id)).collect();
+                self.replay.retain(|(key, _)| !keys.contains(key));
+                // The session ends with its last view.
+                self.stopping = self.views.is_empty();
+            }
+            _ => (),
+        }
+    }
+}
+
+/// Returns the key of the notification `method` replayed for `view_id`.
+fn view_key(method: &str, view_id: &str) -> String {
+    format!("{}:{}", method, view_id)
+}
+
+fn write_line(stream: &mut UnixStream, message: &str) -> io::Result<()> {
+    stream.write_all(message.trim_end().as_bytes())?;
+    stream.write_all(b"\n")?;
+    stream.flush()
+}
+
+/// Runs xi-core behind the Unix socket `socket_path` until its last view is
+/// closed. A single frontend is attached at a time, a new one detaches the
+/// previous one.
+pub fn run(socket_path: &Path) -> io::Result<()> {
+    // Connecting to a running session would detach its frontend, the socket
+    // of a server which didn't stop properly must be removed by the user.
+    if socket_path.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AddrInUse,
+            format!("{} already exists", socket_path.display()),
+        ));
+    }
+
+    let listener = UnixListener::bind(socket_path)?;
+    info!("session server listening on {}", socket_path.display());
+
+    let (client_to_core_writer, core_to_client_reader, _, core_thread) = core::start_xi_core();
+    // Only used to send notifications, its loop never runs.
+    let core = RpcLoop::new(client_to_core_writer.clone()).get_raw_peer();
+    let session = Arc::new(Mutex::new(Session::default()));
+
+    let core_session = session.clone();
+    let core_writer = client_to_core_writer.clone();
+    thread::spawn(move || forward_core_messages(core_to_client_reader, core_writer, &core_session));
+
+    for stream in listener.incoming() {
+        if session.lock().unwrap().stopping {
+            break;
+        }
+
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(err) => {
+                error!("failed to accept a frontend: {}", err);
+                continue;
+            }
+        };
+
+        let connection = match attach(&session, &stream) {
+            Ok(connection) => connection,
+            Err(err) => {
+                error!("failed to attach a frontend: {}", err);
+                continue;
+            }
+        };
+
+        let client = Client {
+            connection,
+            writer: client_to_core_writer.clone(),
+            core: core.clone(),
+            socket_path: socket_path.to_path_buf(),
+        };
+        let session = session.clone();
+        thread::spawn(move || client.forward_messages(stream, &session));
+    }
+
+    core_thread.shutdown();
+    fs::remove_file(socket_path)?;
+    info!("session server stopped");
+    Ok(())
+}
+
+/// Makes `stream` the attached frontend, detaching the previous one.
+fn attach(session: &Mutex<Session>, stream: &UnixStream) -> io::Result<usize> {
+    // The previous frontend stays attached if this one can't be.
+    let stream = stream.try_clone()?;
+    let mut session = session.lock().unwrap();
+
+    if let Some((_, previous)) = session.client.take() {
+        info!("a new frontend attached, the previous one is detached");
+        let _ = previous.shutdown(Shutdown::Both);
+    }
+
+    session.connections += 1;
+    let connection = session.connections;
+    session.client = Some((connection, stream));
+    Ok(connection)
+}
+
+/// Forwards the messages of xi-core to the attached frontend, the requests
+/// being answered with an error through `writer` while none is attached.
+fn forward_core_messages(mut reader: Reader, mut writer: Writer, session: &Mutex<Session>) {
+    loop {
+        let mut message = String::new();
+        match reader.read_line(&mut message) {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let error = session.lock().unwrap().handle_core_message(&message);
+                if let Some(error) = error {
+                    if let Err(err) = writer.write_all(format!("{}\n", error).as_bytes()) {
+                        error!("failed to send to xi-core: {}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// An attached frontend.
+struct Client {
+    connection: usize,
+    /// Forwards the messages of the frontend to xi-core.
+    writer: Writer,
+    /// Sends the notifications of the server to xi-core.
+    core: RawPeer<Writer>,
+    socket_path: PathBuf,
+}
+
+impl Client {
+    fn forward_messages(mut self, stream: UnixStream, session: &Mutex<Session>) {
+        for message in BufReader::new(stream).lines() {
+            let message = match message {
+                Ok(message) => message,
+                Err(_) => break,
+            };
+            let mut parsed: Value = serde_json::from_str(&message).unwrap_or(Value::Null);
+
+            if parsed["method"] == "attach" {
+                self.answer_attach(&parsed["id"], session);
+                continue;
+            }
+
+            session.lock().unwrap().observe_client_message(self.connection, &mut parsed);
+            // The requests are sent with the id given by the session.
+            let message = if parsed.is_null() { message } else { parsed.to_string() };
+            if let Err(err) = self.writer.write_all(format!("{}\n", message).as_bytes()) {
+                error!("failed to send to xi-core: {}", err);
+                break;
+            }
+        }
+
+        let mut session = session.lock().unwrap();
+        if matches!(session.client, Some((attached, _)) if attached == self.connection) {
+            info!("the frontend detached");
+            session.client = None;
+        }
+
+        if session.stopping {
+            // Wakes up the listener, so that it notices the session is over.
+            drop(session);
+            let _ = UnixStream::connect(&self.socket_path);
+        }
+    }
+
+    /// Answers the `attach` request `id` with the open views, after
+    /// replaying the state of the session.
+    fn answer_attach(&self, id: &Value, session: &Mutex<Session>) {
+        let mut session = session.lock().unwrap();
+
+        let replay: Vec<String> =
+            session.replay.iter().map(|(_, message)| message.to_string()).collect();
+        for message in replay {
+            session.send(&message);
+        }
+
+        let response = json!({ "id": id, "result": &session.views });
+        session.send(&response.to_string());
+
+        // Setting the theme again makes xi-core send every line of the views,
+        // the frontend starting with empty views.
+        if let Some(theme_name) = session.theme.clone() {
+            CoreNotification::SetTheme { theme_name }.send(&self.core);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Attaches a new frontend to `session`, returns its end of the socket.
+    fn attach(session: &mut Session) -> (usize, BufReader<UnixStream>) {
+        let (server, client) = UnixStream::pair().unwrap();
+        session.connections += 1;
+        session.client = Some((session.connections, server));
+        (session.connections, BufReader::new(client))
+    }
+
+    fn receive(client: &mut BufReader<UnixStream>) -> Value {
+        let mut line = String::new();
+        client.read_line(&mut line).unwrap();
+        serde_json::from_str(&line).unwrap()
+    }
+
+    fn new_view(session: &mut Session, connection: usize, file_path: &str) -> Value {
+        let mut request = json!({
+            "id": 0,
+            "method": "new_view",
+            "params": { "file_path": file_path },
+        });
+        session.observe_client_message(connection, &mut request);
+        request
+    }
+
+    #[test]
+    fn forwards_the_responses_to_their_frontend() {
+        let mut session = Session::default();
+        let (first, _) = attach(&mut session);
+        let first_request = new_view(&mut session, first, "a.txt");
+        let (second, mut client) = attach(&mut session);
+        let second_request = new_view(&mut session, second, "b.txt");
+        assert_ne!(first_request["id"], second_request["id"]);
+
+        // The response to the detached frontend is dropped.
+        for (request, view_id) in [(&first_request, "view-id-1"), (&second_request, "view-id-2")] {
+            let response = json!({ "id": request["id"], "result": view_id });
+            assert_eq!(session.handle_core_message(&response.to_string()), None);
+        }
+        assert_eq!(receive(&mut client), json!({ "id": 0, "result": "view-id-2" }));
+
+        let paths: Vec<_> = session.views.iter().map(|view| view.file_path.clone()).collect();
+        assert_eq!(paths, vec![Some(String::from("a.txt")), Some(String::from("b.txt"))]);
+    }
+
+    #[test]
+    fn answers_the_requests_without_frontend() {
+        let mut session = Session::default();
+        let request = json!({ "id": 3, "method": "measure_width", "params": [] });
+
+        let error = session.handle_core_message(&request.to_string()).unwrap();
+        let error: Value = serde_json::from_str(&error).unwrap();
+        assert_eq!(error["id"], 3);
+        assert_eq!(error["error"]["code"], NO_FRONTEND);
+    }
+    #[test]
+    fn replays_the_state_of_the_open_views() {
+        let mut session = Session::default();
+        let notifications = [
+            json!({ "method": "def_style", "params": { "id": 2, "fg_color": 1 } }),
+            json!({ "method": "def_style", "params": { "id": 3, "fg_color": 1 } }),
+            json!({ "method": "def_style", "params": { "id": 2, "fg_color": 4 } }),
+            json!({
+                "method": "config_changed",
+                "params": { "view_id": "view-id-1", "changes": { "tab_size": 4, "wrap_width": 0 } },
+            }),
+            json!({
+                "method": "config_changed",
+                "params": { "view_id": "view-id-1", "changes": { "wrap_width": 80 } },
+            }),
+            json!({
+                "method": "config_changed",
+                "params": { "view_id": "view-id-2", "changes": { "tab_size": 2 } },
+            }),
+        ];
+        for notification in &notifications {
+            session.handle_core_message(&notification.to_string());
+        }
+
+        let mut close_view =
+            json!({ "method": "close_view", "params": { "view_id": "view-id-2" } });
+        session.observe_client_message(1, &mut close_view);
+
+        let replay: Vec<&Value> = session.replay.iter().map(|(_, message)| message).collect();
+        assert_eq!(
+            replay,
+            vec![
+                &notifications[1],
+                &notifications[2],
+                &json!({
+                    "method": "config_changed",
+                    "params": {
+                        "view_id": "view-id-1",
+                        "changes": { "tab_size": 4, "wrap_width": 80 },
+                    },
+                }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,41 @@
+//! The views open in the frontend, saved in a session file on exit or with
+//! `:mksession`, and reopened with `--session`.
+
+use std::fs::{self, File};
+use std::io;
+use std::path::Path;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Session {
+    /// The xi-core theme.
+    pub theme: Option<String>,
+    /// The views with a path, the displayed one being the last: the frontend
+    /// displays the view opened last.
+    pub views: Vec<SavedView>,
+}
+
+#[derive(Debug, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+pub struct SavedView {
+    pub path: String,
+    /// The position of the primary cursor, the column being a byte offset.
+    pub line: usize,
+    pub col: usize,
+    /// The first line and column displayed.
+    pub first_line: usize,
+    pub first_col: usize,
+}
+
+impl Session {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Ok(serde_json::from_reader(File::open(path)?)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer_pretty(File::create(path)?, self)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,316 @@
+//! Swap files keeping the unsaved changes of the views, to recover them once
+//! the frontend crashed or was killed.
+//!
+//! The EventController writes the text of the modified views to the swap
+//! directory periodically, and the InputController offers to recover it when
+//! a file with a stale swap file is opened.
+
+use std::cmp;
+use std::fs::{self, File};
+use std::io;
+use std::path::{self, PathBuf};
+use std::process;
+
+/// Above this number of compared pairs of lines, `diff` doesn't look for the
+/// lines kept between the changed ones.
+const MAX_DIFF_CELLS: usize = 4_000_000;
+
+/// The longest name of a swap file, without its extension. The file systems
+/// usually limit the names to 255 bytes, the one of the temporary file
+/// included.
+const MAX_NAME_LEN: usize = 255 - ".swp.tmp".len();
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SwapFile {
+    /// The file being edited.
+    pub path: String,
+    /// The frontend which wrote the swap file.
+    pub pid: u32,
+    /// The RFC 3339 time the swap file was written.
+    pub time: String,
+    /// The text of the view, with its unsaved changes.
+    pub contents: String,
+}
+
+impl SwapFile {
+    /// Returns true if another frontend which wrote the swap file is still
+    /// running, the file being edited there.
+    ///
+    /// A swap file written by this frontend is not live: it is either the one
+    /// of the view reopening the file, or left by a view closed since.
+    pub fn is_live(&self) -> bool {
+        if self.pid == process::id() {
+            return false;
+        }
+
+        // The signal 0 only checks that the process exists.
+        let res = unsafe { libc::kill(self.pid as libc::pid_t, 0) };
+        res == 0 || io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+    }
+}
+
+/// The directory of the swap files, one per edited file.
+#[derive(Debug, Clone)]
+pub struct SwapDir {
+    dir: PathBuf,
+}
+
+impl SwapDir {
+    pub fn new(dir: PathBuf) -> Self {
+        SwapDir { dir }
+    }
+
+    /// Returns the swap file of `file_path`, named after its absolute path
+    /// like `%2Fhome%2Fuser%2Fnotes.txt.swp`.
+    ///
+    /// The names too long only keep the end of the path, after a hash of the
+    /// whole path.
+    fn swap_path(&self, file_path: &str) -> io::Result<PathBuf> {
+        let file_path = path::absolute(file_path)?;
+        let mut name = file_path.to_string_lossy().replace('%', "%25").replace('/', "%2F");
+
+        if name.len() > MAX_NAME_LEN {
+            let hash = format!("{:016x}", fnv1a(name.as_bytes()));
+            let mut start = name.len() - (MAX_NAME_LEN - hash.len() - 1);
+            while !name.is_char_boundary(start) {
+                start += 1;
+            }
+            name = format!("{}-{}", hash, &name[start..]);
+        }
+        Ok(self.dir.join(format!("{}.swp", name)))
+    }
+
+    /// Writes `contents` to the swap file of `file_path`. The swap file is
+    /// replaced at once, a crash never leaves it half written.
+    pub fn write(&self, file_path: &str, contents: &str) -> io::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let swap_path = self.swap_path(file_path)?;
+        let swap_file = SwapFile {
+            path: file_path.to_string(),
+            pid: process::id(),
+            time: chrono::Local::now().to_rfc3339(),
+            contents: contents.to_string(),
+        };
+
+        let tmp_path = swap_path.with_extension("swp.tmp");
+        serde_json::to_writer(File::create(&tmp_path)?, &swap_file)?;
+        fs::rename(&tmp_path, &swap_path)
+    }
+
+    /// Reads the swap file of `file_path`, `None` if there is none.
+    pub fn read(&self, file_path: &str) -> io::Result<Option<SwapFile>> {
+        match File::open(self.swap_path(file_path)?) {
+            Ok(file) => Ok(Some(serde_json::from_reader(file)?)),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn remove(&self, file_path: &str) -> io::Result<()> {
+        match fs::remove_file(self.swap_path(file_path)?) {
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Ok(()),
+            res => res,
+        }
+    }
+}
+
+/// The 64-bit FNV-1a hash of `bytes`, the same in every build unlike the
+/// hashers of the standard library.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Compares `old` and `new` line by line. Every line is returned, prefixed
+/// by `-` if it was removed, `+` if it was added and a space otherwise.
+pub fn diff(old: &str, new: &str) -> String {
+    let old: Vec<&str> = old.lines().collect();
+    let new: Vec<&str> = new.lines().collect();
+
+    // The changes are usually grouped, only the lines between the first and
+    // the last change are compared.
+    let prefix = old.iter().zip(&new).take_while(|(a, b)| a == b).count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    let mut lines: Vec<(char, &str)> = old[..prefix].iter().map(|line| (' ', *line)).collect();
+    lines.extend(diff_lines(
+        &old[prefix..old.len() - suffix],
+        &new[prefix..new.len() - suffix],
+    ));
+    lines.extend(old[old.len() - suffix..].iter().map(|line| (' ', *line)));
+
+    lines
+        .into_iter()
+        .map(|(tag, line)| format!("{}{}\n", tag, line))
+        .collect()
+}
+
+/// Finds the longest sequence of lines kept from `old` to `new`, every other
+/// line being removed or added.
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<(char, &'a str)> {
+    let removed = old.iter().map(|line| ('-', *line));
+    let added = new.iter().map(|line| ('+', *line));
+    if old.len() * new.len() > MAX_DIFF_CELLS {
+        return removed.chain(added).collect();
+    }
+
+    // The length of the longest sequence kept from `old[i..]` to `new[j..]`.
+    let mut kept = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            kept[i][j] = if old[i] == new[j] {
+                kept[i + 1][j + 1] + 1
+            } else {
+                cmp::max(kept[i + 1][j], kept[i][j + 1])
+            };
+        }
+    }
+
+    let (mut i, mut j) = (0, 0);
+    let mut lines = Vec::new();
+    while i < old.len() && j < new.len() {
+        if old[i] == new[j] {
+            lines.push((' ', old[i]));
+            i += 1;
+            j += 1;
+        } else if kept[i + 1][j] >= kept[i][j + 1] {
+            lines.push(('-', old[i]));
+            i += 1;
+        } else {
+            lines.push(('+', new[j]));
+            j += 1;
+        }
+    }
+    lines.extend(old[i..].iter().map(|line| ('-', *line)));
+    lines.extend(new[j..].iter().map(|line| ('+', *line)));
+
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn swap_dir(name: &str) -> SwapDir {
+        let dir = std::env::temp_dir().join(format!("xi-example-swap-{}-{}", process::id(), name));
+        SwapDir::new(dir)
+    }
+
+    fn swap_file(pid: u32) -> SwapFile {
+        SwapFile {
+            path: String::from("/tmp/notes.txt"),
+            pid,
+            time: String::new(),
+            contents: String::new(),
+        }
+    }
+
+    #[test]
+    fn only_other_running_frontends_are_live() {
+        assert!(!swap_file(process::id()).is_live());
+        assert!(swap_file(unsafe { libc::getppid() } as u32).is_live());
+    }
+
+    #[test]
+    fn diff_inserted_lines() {
+        assert_eq!(diff("a\nc\n", "a\nb\nc\n"), " a\n+b\n c\n");
+        assert_eq!(diff("a\n", "a\nb\n"), " a\n+b\n");
+    }
+
+    #[test]
+    fn diff_deleted_lines() {
+        assert_eq!(diff("a\nb\nc\n", "a\nc\n"), " a\n-b\n c\n");
+        assert_eq!(diff("a\nb\n", "b\n"), "-a\n b\n");
+    }
+
+    #[test]
+    fn diff_replaced_lines() {
+        assert_eq!(diff("a\nb\nc\n", "a\nx\nc\n"), " a\n-b\n+x\n c\n");
+        assert_eq!(diff("a\nb\nc\nd\n", "b\nx\nd\n"), "-a\n b\n-c\n+x\n d\n");
+    }
+
+    #[test]
+    fn diff_empty_sides() {
+        assert_eq!(diff("", ""), "");
+        assert_eq!(diff("", "a\nb\n"), "+a\n+b\n");
+        assert_eq!(diff("a\nb\n", ""), "-a\n-b\n");
+        assert_eq!(diff("a\n", "a\n"), " a\n");
+    }
+
+    #[test]
+    fn diff_over_the_limit() {
+        // Every line changed but the one in the middle, which is only found
+        // under the limit.
+        let texts = |len: usize| {
+            let old: Vec<String> = (0..len).map(|i| format!("old {}", i)).collect();
+            let mut new: Vec<String> = (0..len).map(|i| format!("new {}", i)).collect();
+            new[len / 2] = old[len / 2].clone();
+            (old.join("\n"), new.join("\n"))
+        };
+
+        let (old, new) = texts(2001);
+        let lines: Vec<String> = diff(&old, &new).lines().map(String::from).collect();
+        assert_eq!(lines.len(), 4002);
+        assert!(lines[..2001].iter().all(|line| line.starts_with('-')));
+        assert!(lines[2001..].iter().all(|line| line.starts_with('+')));
+
+        let (old, new) = texts(1999);
+        let kept: Vec<String> = diff(&old, &new)
+            .lines()
+            .filter(|line| line.starts_with(' '))
+            .map(String::from)
+            .collect();
+        assert_eq!(kept, vec![" old 999"]);
+    }
+
+    #[test]
+    fn escapes_the_swap_names() {
+        let swap_dir = swap_dir("names");
+        let name = |path: &str| swap_dir.swap_path(path).unwrap().file_name().unwrap().to_owned();
+        assert_eq!(name("/home/user/notes.txt"), "%2Fhome%2Fuser%2Fnotes.txt.swp");
+        assert_eq!(name("/home/100%/notes.txt"), "%2Fhome%2F100%25%2Fnotes.txt.swp");
+        assert_ne!(name("/tmp/a%/b"), name("/tmp/a/%b"));
+    }
+
+    #[test]
+    fn shortens_the_long_swap_names() {
+        let swap_dir = swap_dir("long-names");
+        let dir = "/tmp/".to_string() + &"é".repeat(150);
+        let (first, second) = (format!("{}/a.txt", dir), format!("/var{}/a.txt", dir));
+
+        let name = swap_dir.swap_path(&first).unwrap().file_name().unwrap().to_owned();
+        let name = name.to_str().unwrap();
+        assert!(name.len() <= 255 - ".tmp".len());
+        assert!(name.ends_with("%2Fa.txt.swp"));
+        assert_ne!(swap_dir.swap_path(&first).unwrap(), swap_dir.swap_path(&second).unwrap());
+
+        swap_dir.write(&first, "first").unwrap();
+        swap_dir.write(&second, "second").unwrap();
+        assert_eq!(swap_dir.read(&first).unwrap().unwrap().contents, "first");
+        assert_eq!(swap_dir.read(&second).unwrap().unwrap().contents, "second");
+        fs::remove_dir_all(&swap_dir.dir).unwrap();
+    }
+
+    #[test]
+    fn reads_the_written_swap_files() {
+        let swap_dir = swap_dir("round-trip");
+        swap_dir.write("/tmp/a%/b", "first").unwrap();
+        swap_dir.write("/tmp/a/%b", "second").unwrap();
+
+        let swap_file = swap_dir.read("/tmp/a%/b").unwrap().unwrap();
+        assert_eq!(swap_file.path, "/tmp/a%/b");
+        assert_eq!(swap_file.contents, "first");
+        assert_eq!(swap_dir.read("/tmp/a/%b").unwrap().unwrap().contents, "second");
+
+        swap_dir.remove("/tmp/a%/b").unwrap();
+        assert!(swap_dir.read("/tmp/a%/b").unwrap().is_none());
+        swap_dir.remove("/tmp/a/%b").unwrap();
+        let _ = fs::remove_dir(&swap_dir.dir);
+    }
+}
@@ -0,0 +1,65 @@
+use super::Harness;
+
+#[test]
+fn insert_text() {
+    let mut harness = Harness::open("world\n");
+    harness.type_keys("i h e l l o <space> <esc>");
+
+    let outcome = harness.finish();
+    assert_eq!(outcome.contents, "hello world\n");
+    assert!(outcome.screen[0].ends_with("hello world"));
+}
+
+#[test]
+fn insert_line_below() {
+    let mut harness = Harness::open("first\n");
+    harness.type_keys("o s e c o n d <esc>");
+
+    let outcome = harness.finish();
+    assert_eq!(outcome.contents, "first\nsecond\n");
+    assert!(outcome.screen[0].ends_with("first"));
+    assert!(outcome.screen[1].ends_with("second"));
+}
+
+#[test]
+fn delete_with_count() {
+    let mut harness = Harness::open("abcdef\n");
+    harness.type_keys("3 x");
+
+    assert_eq!(harness.finish().contents, "def\n");
+}
+
+#[test]
+fn insert_with_count() {
+    let mut harness = Harness::open("!\n");
+    harness.type_keys("3 i h i <esc>");
+
+    assert_eq!(harness.finish().contents, "hihihi!\n");
+}
+
+#[test]
+fn repeat_last_change() {
+    let mut harness = Harness::open("ab\ncd\n");
+    harness.type_keys("x j .");
+
+    assert_eq!(harness.finish().contents, "b\nd\n");
+}
+
+#[test]
+fn replay_macro() {
+    let mut harness = Harness::open("abc\n");
+    harness.type_keys("q a x q @ a");
+
+    assert_eq!(harness.finish().contents, "c\n");
+}
+
+#[test]
+fn status_bar_shows_mode() {
+    let mut harness = Harness::open("text\n");
+    harness.type_keys("i");
+    harness.wait_for_screen(|rows| rows.last().is_some_and(|bar| bar.contains("INSERT")));
+
+    let outcome = harness.finish();
+    let status_bar = outcome.screen.last().unwrap();
+    assert!(status_bar.contains("NORMAL"));
+}
@@ -0,0 +1,86 @@
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+use serde_json::Value;
+use xi_rpc::{Callback, Error, Peer, RemoteError};
+
+use crate::protocol::{CoreNotification, CoreRequest};
+
+/// A `Peer` standing for xi-core: it records the messages sent to it and
+/// answers the requests with the responses given by the test.
+///
+/// The clones share the same recording and responses.
+#[derive(Clone, Default)]
+pub struct MockPeer {
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Default)]
+struct State {
+    notifications: Vec<CoreNotification>,
+    requests: Vec<CoreRequest>,
+    responses: Vec<(CoreRequest, Result<Value, RemoteError>)>,
+}
+
+impl MockPeer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers `request` with `response` from now on.
+    pub fn respond(&self, request: CoreRequest, response: Result<Value, RemoteError>) {
+        let mut state = self.state.lock().unwrap();
+        state.responses.retain(|(scripted, _)| *scripted != request);
+        state.responses.push((request, response));
+    }
+
+    /// Returns the notifications sent so far, in order.
+    pub fn notifications(&self) -> Vec<CoreNotification> {
+        self.state.lock().unwrap().notifications.clone()
+    }
+
+    /// Returns the requests sent so far, in order.
+    pub fn requests(&self) -> Vec<CoreRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+}
+
+impl Peer for MockPeer {
+    fn box_clone(&self) -> Box<dyn Peer> {
+        Box::new(self.clone())
+    }
+
+    fn send_rpc_notification(&self, method: &str, params: &Value) {
+        let notification = json!({ "method": method, "params": params });
+        let notification = serde_json::from_value(notification.clone())
+            .unwrap_or_else(|err| panic!("unknown notification {}: {}", notification, err));
+        self.state.lock().unwrap().notifications.push(notification);
+    }
+
+    fn send_rpc_request_async(&self, method: &str, params: &Value, f: Box<dyn Callback>) {
+        f.call(self.send_rpc_request(method, params));
+    }
+
+    /// Panics if no response was given for the request.
+    fn send_rpc_request(&self, method: &str, params: &Value) -> Result<Value, Error> {
+        let request = json!({ "method": method, "params": params });
+        let request: CoreRequest = serde_json::from_value(request.clone())
+            .unwrap_or_else(|err| panic!("unknown request {}: {}", request, err));
+
+        let mut state = self.state.lock().unwrap();
+        state.requests.push(request.clone());
+        match state.responses.iter().find(|(scripted, _)| *scripted == request) {
+            Some((_, Ok(result))) => Ok(result.clone()),
+            Some((_, Err(err))) => Err(Error::RemoteError(err.clone())),
+            None => panic!("no response for the request {:?}", request),
+        }
+    }
+
+    fn request_is_pending(&self) -> bool {
+        false
+    }
+
+    fn schedule_idle(&self, _token: usize) {}
+
+    fn schedule_timer(&self, _after: Instant, _token: usize) {}
+}
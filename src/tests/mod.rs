@@ -0,0 +1,161 @@
+//! Tests running the frontend headlessly with the embedded xi-core: the keys
+//! are typed by a scripted keyboard and the views are rendered on an
+//! in-memory terminal.
+//!
+//! The unit tests of the frontend replace xi-core by a `MockPeer`.
+
+mod editing;
+mod mock;
+
+pub use self::mock::MockPeer;
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use xi_rpc::{RawPeer, RpcLoop};
+
+use crate::channel::Writer;
+use crate::config::Config;
+use crate::core::{self, CoreThread};
+use crate::events::EventController;
+use crate::protocol::{CoreNotification, CoreRequest, EditMethod};
+use crate::ui::keyboard::ReplayKeyboard;
+use crate::ui::terminal::MemoryTerminal;
+use crate::ui::InputController;
+
+const COLUMNS: u16 = 80;
+const ROWS: u16 = 24;
+
+/// How long `wait_for_screen` waits for the EventController.
+const SCREEN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Numbers the files opened by the tests, which run in parallel.
+static FILE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// A frontend editing a file, driven by the tests.
+pub struct Harness {
+    input_controller: InputController,
+    core: RawPeer<Writer>,
+    terminal: MemoryTerminal,
+    event_thread: JoinHandle<()>,
+    core_thread: CoreThread,
+    file_path: PathBuf,
+}
+
+/// The state of the frontend once the harness is stopped.
+pub struct Outcome {
+    /// The rows of the screen, without their trailing spaces.
+    pub screen: Vec<String>,
+    /// The contents of the file, saved before stopping.
+    pub contents: String,
+}
+
+impl Harness {
+    /// Opens a new file containing `text`.
+    pub fn open(text: &str) -> Self {
+        let file_name = format!(
+            "xi-example-test-{}-{}.txt",
+            std::process::id(),
+            FILE_COUNT.fetch_add(1, Ordering::SeqCst)
+        );
+        let file_path = std::env::temp_dir().join(file_name);
+        fs::write(&file_path, text).expect("failed to create the test file");
+
+        let (client_to_core_writer, core_to_client_reader, client_to_client_writer, core_thread) =
+            core::start_xi_core();
+        let mut front_event_loop = RpcLoop::new(client_to_core_writer);
+        let core = front_event_loop.get_raw_peer();
+        CoreNotification::ClientStarted {
+            config_dir: None,
+            client_extras_dir: None,
+        }
+        .send(&core);
+
+        let terminal = MemoryTerminal::new(COLUMNS, ROWS);
+        let event_terminal = terminal.clone();
+        let event_thread = thread::spawn(move || {
+            let mut event_handler =
+                EventController::new(Config::default(), Box::new(event_terminal));
+            front_event_loop
+                .mainloop(|| core_to_client_reader, &mut event_handler)
+                .unwrap();
+            event_handler.close_views(&front_event_loop.get_raw_peer());
+        });
+
+        let keyboard = ReplayKeyboard::new(Vec::new());
+        let mut input_controller =
+            InputController::new(Box::new(keyboard), client_to_client_writer);
+        input_controller
+            .open_file(&core, file_path.to_str().unwrap())
+            .expect("failed to open the test file");
+
+        Harness {
+            input_controller,
+            core,
+            terminal,
+            event_thread,
+            core_thread,
+            file_path,
+        }
+    }
+
+    /// Types the keys described in `keys`, separated by spaces as in the
+    /// keymap, like `"i h i <esc>"`.
+    pub fn type_keys(&mut self, keys: &str) -> &mut Self {
+        let descriptions: Vec<&str> = keys.split_whitespace().collect();
+        let keyboard = ReplayKeyboard::from_descriptions(&descriptions)
+            .unwrap_or_else(|| panic!("invalid keys: {}", keys));
+
+        self.input_controller.set_keyboard(Box::new(keyboard));
+        self.input_controller
+            .start_keyboard_event_loop(&self.core)
+            .unwrap();
+        self
+    }
+
+    /// Returns the rows of the screen once `ready` accepts them, the
+    /// EventController rendering the keys typed in its own thread. Panics if
+    /// it doesn't happen in time.
+    pub fn wait_for_screen<F: Fn(&[String]) -> bool>(&self, ready: F) -> Vec<String> {
+        let deadline = Instant::now() + SCREEN_TIMEOUT;
+        loop {
+            let rows = self.terminal.rows();
+            if ready(&rows) {
+                return rows;
+            }
+            if Instant::now() > deadline {
+                panic!("unexpected screen:\n{}", rows.join("\n"));
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    /// Saves the file and stops the frontend, once the keys typed are
+    /// handled.
+    pub fn finish(mut self) -> Outcome {
+        self.type_keys("<esc> <esc> : w <enter>");
+
+        // xi-core handles the messages in order: once it answered, the
+        // updates of the keys typed before are sent to the EventController.
+        CoreRequest::edit(self.input_controller.view_id(), EditMethod::Copy)
+            .send(&self.core)
+            .expect("xi-core didn't answer");
+
+        // The same shutdown as the frontend: the file is saved once xi-core
+        // is stopped.
+        self.input_controller.shutdown();
+        self.event_thread.join().expect("the EventController panicked");
+        self.core_thread.shutdown();
+
+        let contents = fs::read_to_string(&self.file_path).expect("failed to read the test file");
+        let _ = fs::remove_file(&self.file_path);
+
+        Outcome {
+            screen: self.terminal.rows(),
+            contents,
+        }
+    }
+}
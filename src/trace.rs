@@ -0,0 +1,109 @@
+//! Records the messages exchanged with xi-core in a JSONL trace, and replays
+//! the messages received by the frontend to reproduce its screen.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Cursor, LineWriter, Write};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use serde_json::Value;
+use xi_rpc::RpcLoop;
+
+use crate::channel::Tap;
+use crate::config::Config;
+use crate::events::EventController;
+use crate::ui::terminal::MemoryTerminal;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Direction {
+    /// Sent to xi-core by the frontend.
+    ToCore,
+    /// Read by the EventController: the messages of xi-core and the
+    /// notifications of the InputController.
+    ToFrontend,
+}
+
+/// A line of a trace.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Record {
+    /// The RFC 3339 time the message was sent or received.
+    pub time: String,
+    pub direction: Direction,
+    /// The message, as a string if it isn't valid JSON.
+    pub message: Value,
+}
+
+impl Record {
+    pub fn new(time: String, direction: Direction, message: &[u8]) -> Self {
+        let message = match serde_json::from_slice(message) {
+            Ok(message) => message,
+            Err(_) => Value::String(String::from_utf8_lossy(message).trim_end().to_string()),
+        };
+
+        Record {
+            time,
+            direction,
+            message,
+        }
+    }
+}
+
+/// Writes the messages given to its taps to a trace file.
+pub struct Recorder {
+    file: Mutex<LineWriter<File>>,
+}
+
+impl Recorder {
+    /// Creates the trace file `path`, replacing the previous trace.
+    pub fn create(path: &Path) -> io::Result<Arc<Self>> {
+        let file = LineWriter::new(File::create(path)?);
+        Ok(Arc::new(Self {
+            file: Mutex::new(file),
+        }))
+    }
+
+    /// Returns a tap recording the messages going in `direction`.
+    pub fn tap(self: &Arc<Self>, direction: Direction) -> Tap {
+        let recorder = self.clone();
+        Arc::new(move |message| recorder.record(direction, message))
+    }
+
+    fn record(&self, direction: Direction, message: &[u8]) {
+        let record = Record::new(chrono::Local::now().to_rfc3339(), direction, message);
+
+        // The lines are flushed one by one, the trace is complete even if
+        // the frontend crashes.
+        let mut file = self.file.lock().unwrap();
+        let res = serde_json::to_writer(&mut *file, &record)
+            .map_err(io::Error::from)
+            .and_then(|_| file.write_all(b"\n"));
+        if let Err(err) = res {
+            error!("failed to record a message: {}", err);
+        }
+    }
+}
+
+/// Replays the messages read by the EventController in the trace `path`,
+/// rendering them on a `columns` x `rows` screen, and returns the screen.
+///
+/// The messages sent to xi-core during the replay are dropped.
+pub fn replay(path: &Path, config: Config, columns: u16, rows: u16) -> io::Result<MemoryTerminal> {
+    let mut messages = Vec::new();
+    for line in BufReader::new(File::open(path)?).lines() {
+        let record: Record = serde_json::from_str(&line?)?;
+        if record.direction == Direction::ToFrontend {
+            serde_json::to_writer(&mut messages, &record.message)?;
+            messages.push(b'\n');
+        }
+    }
+
+    let terminal = MemoryTerminal::new(columns, rows);
+    let mut event_handler = EventController::new(config, Box::new(terminal.clone()));
+    let mut event_loop = RpcLoop::new(io::sink());
+    event_loop
+        .mainloop(|| Cursor::new(messages), &mut event_handler)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", err)))?;
+
+    Ok(terminal)
+}
@@ -1,7 +1,11 @@
-mod rpc;
+pub mod rpc;
+
+#[cfg(test)]
+mod tests;
 
 use super::KeyStroke;
 use crate::core::ClientToClientWriter;
+use crate::error::Result;
 
 use xi_rpc::Peer;
 
@@ -9,10 +13,18 @@ use xi_rpc::Peer;
 pub enum Response {
     Continue,
     Stop,
+    /// Stops the keyboard loop, leaving the views open in the session.
+    Detach,
     SwitchToInsertMode,
     SwitchToNormalMode,
     SwitchToVisualMode,
     SwitchToActionMode,
+    SwitchToPickerMode,
+    RepeatLastChange,
+    ToggleMacroRecording,
+    PlayMacro,
+    /// Lists the messages shown so far, see `:messages`.
+    ShowMessages,
 }
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq)]
@@ -36,16 +48,38 @@ pub enum Action {
     PageUp,
     PageDown,
 
+    GotoLine(usize),
+    GotoStart,
+    GotoEnd,
+    LineStart,
+    LineEnd,
+    FirstNonBlank,
+    MatchingBracket,
+
+    JumpBack,
+    JumpForward,
+
     MoveUpAndSelect,
     MoveDownAndSelect,
     MoveLeftAndSelect,
     MoveRightAndSelect,
     MoveWordRightAndSelect,
     MoveWordLeftAndSelect,
+    GotoStartAndSelect,
+    GotoEndAndSelect,
+    LineStartAndSelect,
+    LineEndAndSelect,
+
+    AddCursorAbove,
+    AddCursorBelow,
+    SelectNextOccurrence,
+    SplitSelectionIntoLines,
+    CollapseSelections,
 
     YankSelection,
     DeleteSelection,
     DeleteSelectionAndPaste,
+    ChangeSelection,
 
     Paste,
 
@@ -55,6 +89,19 @@ pub enum Action {
     DeleteBackward,
     DeleteForward,
 
+    RepeatLastChange,
+
+    ToggleMacroRecording,
+    PlayMacro,
+
+    ToggleLineNumbers,
+    ToggleSoftWrap,
+
+    PickerNext,
+    PickerPrevious,
+    PickerSelect,
+    PickerCancel,
+
     // Custom for the insert mode. Not configurable
     InsertKeyStroke(KeyStroke),
 }
@@ -65,15 +112,15 @@ impl Action {
         view_id: &str,
         core: &dyn Peer,
         front_event_writer: &mut ClientToClientWriter,
-    ) -> Response {
+    ) -> Result<Response> {
         match self {
             Action::WriteToFile => rpc::write_to_file(view_id, front_event_writer),
-            Action::Quite => rpc::quite(view_id, core),
+            Action::Quite => rpc::quite(),
 
-            Action::SwitchToInsertMode => Response::SwitchToInsertMode,
-            Action::SwitchToVisualMode => Response::SwitchToVisualMode,
-            Action::SwitchToActionMode => Response::SwitchToActionMode,
-            Action::SwitchToNormalMode => Response::SwitchToNormalMode,
+            Action::SwitchToInsertMode => Ok(Response::SwitchToInsertMode),
+            Action::SwitchToVisualMode => Ok(Response::SwitchToVisualMode),
+            Action::SwitchToActionMode => Ok(Response::SwitchToActionMode),
+            Action::SwitchToNormalMode => Ok(Response::SwitchToNormalMode),
 
             Action::MoveUp => rpc::move_up(view_id, core),
             Action::MoveDown => rpc::move_down(view_id, core),
@@ -86,16 +133,38 @@ impl Action {
             Action::PageUp => rpc::page_up(view_id, core),
             Action::PageDown => rpc::page_down(view_id, core),
 
+            Action::GotoLine(line) => rpc::goto_line(view_id, line, core, front_event_writer),
+            Action::GotoStart => rpc::goto_start(view_id, core, front_event_writer),
+            Action::GotoEnd => rpc::goto_end(view_id, core, front_event_writer),
+            Action::LineStart => rpc::line_start(view_id, core),
+            Action::LineEnd => rpc::line_end(view_id, core),
+            Action::FirstNonBlank => rpc::first_non_blank(view_id, front_event_writer),
+            Action::MatchingBracket => rpc::matching_bracket(view_id, front_event_writer),
+
+            Action::JumpBack => rpc::jump_back(view_id, front_event_writer),
+            Action::JumpForward => rpc::jump_forward(view_id, front_event_writer),
+
             Action::MoveUpAndSelect => rpc::move_up_and_select(view_id, core),
             Action::MoveDownAndSelect => rpc::move_down_and_select(view_id, core),
             Action::MoveLeftAndSelect => rpc::move_left_and_select(view_id, core),
             Action::MoveRightAndSelect => rpc::move_right_and_select(view_id, core),
             Action::MoveWordRightAndSelect => rpc::move_word_right_and_select(view_id, core),
             Action::MoveWordLeftAndSelect => rpc::move_word_left_and_select(view_id, core),
+            Action::GotoStartAndSelect => rpc::goto_start_and_select(view_id, core),
+            Action::GotoEndAndSelect => rpc::goto_end_and_select(view_id, core),
+            Action::LineStartAndSelect => rpc::line_start_and_select(view_id, core),
+            Action::LineEndAndSelect => rpc::line_end_and_select(view_id, core),
+
+            Action::AddCursorAbove => rpc::add_cursor_above(view_id, core),
+            Action::AddCursorBelow => rpc::add_cursor_below(view_id, core),
+            Action::SelectNextOccurrence => rpc::select_next_occurrence(view_id, core),
+            Action::SplitSelectionIntoLines => rpc::split_selection_into_lines(view_id, core),
+            Action::CollapseSelections => rpc::collapse_selections(view_id, core),
 
             Action::YankSelection => rpc::yank_selection(view_id, core),
             Action::DeleteSelection => rpc::cute_selection(view_id, core),
             Action::DeleteSelectionAndPaste => rpc::cute_selection_and_paste(view_id, core),
+            Action::ChangeSelection => rpc::change_selection(view_id, core),
 
             Action::Paste => rpc::paste(view_id, core),
 
@@ -105,6 +174,19 @@ impl Action {
 
             Action::DeleteBackward => rpc::delete_backward(view_id, core),
             Action::DeleteForward => rpc::delete_forward(view_id, core),
+
+            Action::RepeatLastChange => Ok(Response::RepeatLastChange),
+
+            Action::ToggleMacroRecording => Ok(Response::ToggleMacroRecording),
+            Action::PlayMacro => Ok(Response::PlayMacro),
+
+            Action::ToggleLineNumbers => rpc::toggle_line_numbers(front_event_writer),
+            Action::ToggleSoftWrap => rpc::toggle_soft_wrap(front_event_writer),
+
+            Action::PickerNext => rpc::picker_move(1, front_event_writer),
+            Action::PickerPrevious => rpc::picker_move(-1, front_event_writer),
+            Action::PickerSelect => rpc::picker_select(front_event_writer),
+            Action::PickerCancel => rpc::picker_cancel(front_event_writer),
         }
     }
 
@@ -122,17 +204,42 @@ impl Action {
             "move_down" => Some(Action::MoveDown),
             "move_left" => Some(Action::MoveLeft),
             "move_right" => Some(Action::MoveRight),
+            "move_word_right" => Some(Action::MoveWordRight),
+            "move_word_left" => Some(Action::MoveWordLeft),
             "page_up" => Some(Action::PageUp),
             "page_down" => Some(Action::PageDown),
 
+            "goto_start" => Some(Action::GotoStart),
+            "goto_end" => Some(Action::GotoEnd),
+            "line_start" => Some(Action::LineStart),
+            "line_end" => Some(Action::LineEnd),
+            "first_non_blank" => Some(Action::FirstNonBlank),
+            "matching_bracket" => Some(Action::MatchingBracket),
+
+            "jump_back" => Some(Action::JumpBack),
+            "jump_forward" => Some(Action::JumpForward),
+
             "move_up_and_select" => Some(Action::MoveUpAndSelect),
             "move_down_and_select" => Some(Action::MoveDownAndSelect),
             "move_left_and_select" => Some(Action::MoveLeftAndSelect),
             "move_right_and_select" => Some(Action::MoveRightAndSelect),
+            "move_word_right_and_select" => Some(Action::MoveWordRightAndSelect),
+            "move_word_left_and_select" => Some(Action::MoveWordLeftAndSelect),
+            "goto_start_and_select" => Some(Action::GotoStartAndSelect),
+            "goto_end_and_select" => Some(Action::GotoEndAndSelect),
+            "line_start_and_select" => Some(Action::LineStartAndSelect),
+            "line_end_and_select" => Some(Action::LineEndAndSelect),
+
+            "add_cursor_above" => Some(Action::AddCursorAbove),
+            "add_cursor_below" => Some(Action::AddCursorBelow),
+            "select_next_occurrence" => Some(Action::SelectNextOccurrence),
+            "split_selection_into_lines" => Some(Action::SplitSelectionIntoLines),
+            "collapse_selections" => Some(Action::CollapseSelections),
 
             "yank_selection" => Some(Action::YankSelection),
             "delete_selection" => Some(Action::DeleteSelection),
-            "delete_selection_and_past" => Some(Action::DeleteSelectionAndPaste),
+            "delete_selection_and_paste" => Some(Action::DeleteSelectionAndPaste),
+            "change_selection" => Some(Action::ChangeSelection),
 
             "paste" => Some(Action::Paste),
 
@@ -142,6 +249,81 @@ impl Action {
             "delete_backward" => Some(Action::DeleteBackward),
             "delete_forward" => Some(Action::DeleteForward),
 
+            "repeat_last_change" => Some(Action::RepeatLastChange),
+
+            "toggle_macro_recording" => Some(Action::ToggleMacroRecording),
+            "play_macro" => Some(Action::PlayMacro),
+
+            "toggle_line_numbers" => Some(Action::ToggleLineNumbers),
+            "toggle_soft_wrap" => Some(Action::ToggleSoftWrap),
+
+            "picker_next" => Some(Action::PickerNext),
+            "picker_previous" => Some(Action::PickerPrevious),
+            "picker_select" => Some(Action::PickerSelect),
+            "picker_cancel" => Some(Action::PickerCancel),
+
+            _ => None,
+        }
+    }
+
+    /// Returns true for the actions waiting for a motion when they are
+    /// used from the normal mode, like `d` in `dw`.
+    pub fn is_operator(self) -> bool {
+        matches!(
+            self,
+            Action::YankSelection | Action::DeleteSelection | Action::ChangeSelection
+        )
+    }
+
+    /// Returns true for the actions switching to the insert mode to insert
+    /// the text typed next.
+    pub fn enters_insert_mode(self) -> bool {
+        matches!(
+            self,
+            Action::SwitchToInsertMode | Action::InsertLineBelow | Action::InsertLineAbove
+        )
+    }
+
+    /// Returns true for the actions modifying the buffer content.
+    pub fn is_change(self) -> bool {
+        matches!(
+            self,
+            Action::SwitchToInsertMode
+                | Action::DeleteSelection
+                | Action::DeleteSelectionAndPaste
+                | Action::ChangeSelection
+                | Action::Paste
+                | Action::InsertLineBelow
+                | Action::InsertLineAbove
+                | Action::DeleteBackward
+                | Action::DeleteForward
+                | Action::InsertKeyStroke(_)
+        )
+    }
+
+    /// Returns the selecting counterpart of a motion, used to apply an
+    /// operator over the text covered by the motion.
+    pub fn with_selection(self) -> Option<Action> {
+        match self {
+            Action::MoveUp => Some(Action::MoveUpAndSelect),
+            Action::MoveDown => Some(Action::MoveDownAndSelect),
+            Action::MoveLeft => Some(Action::MoveLeftAndSelect),
+            Action::MoveRight => Some(Action::MoveRightAndSelect),
+            Action::MoveWordRight => Some(Action::MoveWordRightAndSelect),
+            Action::MoveWordLeft => Some(Action::MoveWordLeftAndSelect),
+            Action::GotoStart => Some(Action::GotoStartAndSelect),
+            Action::GotoEnd => Some(Action::GotoEndAndSelect),
+            Action::LineStart => Some(Action::LineStartAndSelect),
+            Action::LineEnd => Some(Action::LineEndAndSelect),
+            _ => None,
+        }
+    }
+
+    /// Returns the action taking the count typed before it as an argument,
+    /// like `42G` going to the line 42, instead of being repeated.
+    pub fn with_count(self, count: usize) -> Option<Action> {
+        match self {
+            Action::GotoStart | Action::GotoEnd => Some(Action::GotoLine(count)),
             _ => None,
         }
     }
@@ -1,254 +1,515 @@
+use super::Response;
 use crate::core::ClientToClientWriter;
-use crate::input_controller::keyboard::KeyStroke;
-use crate::input_controller::{Response, PASTE_BUFFER};
-
+use crate::error::{Error, Result};
+use crate::logging::{self, Filter};
+use crate::protocol::{
+    ClientNotification, ConfigDomain, CoreNotification, CoreRequest, EditMethod, PluginCommand,
+    SelectionModifier,
+};
+use crate::ui::keyboard::KeyStroke;
+use crate::ui::messages::Severity;
+use crate::ui::picker::PickerKind;
+use crate::ui::PASTE_BUFFER;
+
+use serde_json::Value;
 use xi_rpc::Peer;
 
-pub fn insert_keystroke(view_id: &str, key: KeyStroke, core: &dyn Peer) -> Response {
+fn edit(view_id: &str, method: EditMethod, core: &dyn Peer) {
+    CoreNotification::edit(view_id, method).send(core);
+}
+
+pub fn insert_keystroke(view_id: &str, key: KeyStroke, core: &dyn Peer) -> Result<Response> {
     let output = match key {
         KeyStroke::Char(c) => c.to_string(),
         KeyStroke::KeySpace => ' '.to_string(),
         _ => String::from("<?>"),
     };
 
-    core.send_rpc_notification(
-        "edit",
-        &json!({
-            "method": "insert",
-            "view_id": view_id,
-            "params": {
-                "chars": output,
-            }
-        }),
-    );
-    Response::Continue
+    edit(view_id, EditMethod::Insert { chars: output }, core);
+    Ok(Response::Continue)
 }
 
-pub fn quite(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification("close_view", &json!({ "view_id": view_id }));
-    core.send_rpc_notification("exit", &json!({}));
-
-    Response::Stop
+/// Stops the keyboard loop, the views are closed during the shutdown.
+pub fn quite() -> Result<Response> {
+    Ok(Response::Stop)
 }
 
-pub fn write_to_file(view_id: &str, core: &mut ClientToClientWriter) -> Response {
-    core.send_rpc_notification("write_to_file", &json!({ "view_id": view_id }));
+pub fn detach() -> Result<Response> {
+    Ok(Response::Detach)
+}
 
-    Response::SwitchToNormalMode
+pub fn show_messages() -> Result<Response> {
+    Ok(Response::ShowMessages)
 }
 
-pub fn delete_backward(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "delete_backward", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn write_to_file(view_id: &str, core: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::WriteToFile {
+        view_id: view_id.to_string(),
+    }
+    .send(core)?;
+
+    Ok(Response::SwitchToNormalMode)
 }
 
-pub fn delete_forward(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "delete_forward", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn delete_backward(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::DeleteBackward, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_up(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification("edit", &json!({ "method": "move_up", "view_id": view_id}));
-    Response::Continue
+pub fn delete_forward(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::DeleteForward, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_down(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification("edit", &json!({ "method": "move_down", "view_id": view_id}));
-    Response::Continue
+pub fn move_up(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveUp, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_left(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification("edit", &json!({ "method": "move_left", "view_id": view_id}));
-    Response::Continue
+pub fn move_down(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveDown, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_right(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_right", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn move_left(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveLeft, core);
+    Ok(Response::Continue)
 }
 
-pub fn page_up(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "scroll_page_up", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn move_right(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveRight, core);
+    Ok(Response::Continue)
 }
 
-pub fn page_down(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "scroll_page_down", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn page_up(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::ScrollPageUp, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_word_right(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_word_right", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn page_down(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::ScrollPageDown, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_word_left(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_word_left", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn move_word_right(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveWordRight, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_up_and_select(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_up_and_modify_selection", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn move_word_left(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveWordLeft, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_down_and_select(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_down_and_modify_selection", "view_id": view_id}),
-    );
-    Response::Continue
+fn push_jump(view_id: &str, front_event_writer: &mut ClientToClientWriter) -> Result<()> {
+    ClientNotification::PushJump {
+        view_id: view_id.to_string(),
+    }
+    .send(front_event_writer)
 }
 
-pub fn move_left_and_select(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_left_and_modify_selection", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn goto_line(
+    view_id: &str,
+    line: usize,
+    core: &dyn Peer,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    push_jump(view_id, front_event_writer)?;
+    // The lines are numbered from 1 for the user but from 0 for xi-core.
+    edit(view_id, EditMethod::GotoLine { line: line.saturating_sub(1) }, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_right_and_select(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_right_and_modify_selection", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn goto_start(
+    view_id: &str,
+    core: &dyn Peer,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    push_jump(view_id, front_event_writer)?;
+    edit(view_id, EditMethod::MoveToBeginningOfDocument, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_word_right_and_select(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_word_right_and_modify_selection", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn goto_end(
+    view_id: &str,
+    core: &dyn Peer,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    push_jump(view_id, front_event_writer)?;
+    edit(view_id, EditMethod::MoveToEndOfDocument, core);
+    Ok(Response::Continue)
 }
 
-pub fn move_word_left_and_select(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "move_word_left_and_modify_selection", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn line_start(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveToLeftEndOfLine, core);
+    Ok(Response::Continue)
 }
 
-pub fn insert_newline(view_id: &str, core: &dyn Peer) -> Response {
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "insert_newline", "view_id": view_id}),
-    );
-    Response::Continue
+pub fn line_end(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveToRightEndOfLine, core);
+    Ok(Response::Continue)
 }
 
-pub fn yank_selection(view_id: &str, core: &dyn Peer) -> Response {
-    let res = core.send_rpc_request("edit", &json!({ "method": "copy", "view_id": view_id}));
-    if let Ok(paste_buffer) = res {
-        let mut buffer = PASTE_BUFFER.lock().unwrap();
-        *buffer = Some(String::from(paste_buffer.as_str().unwrap()));
-    } else {
-        error!("failed to copy selection: {:?}", res.unwrap_err());
+// The text of the lines is only known by the EventController so it computes
+// the position of the following motions itself.
+
+pub fn first_non_blank(
+    view_id: &str,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::MoveToFirstNonBlank {
+        view_id: view_id.to_string(),
     }
+    .send(front_event_writer)?;
+    Ok(Response::Continue)
+}
 
-    // Remove the selection
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "collapse_selections", "view_id": view_id}),
-    );
+pub fn matching_bracket(
+    view_id: &str,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::MoveToMatchingBracket {
+        view_id: view_id.to_string(),
+    }
+    .send(front_event_writer)?;
+    Ok(Response::Continue)
+}
 
-    Response::SwitchToNormalMode
+pub fn jump_back(view_id: &str, front_event_writer: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::JumpBack {
+        view_id: view_id.to_string(),
+    }
+    .send(front_event_writer)?;
+    Ok(Response::Continue)
 }
 
-pub fn cute_selection(view_id: &str, core: &dyn Peer) -> Response {
-    let cut_res = core.send_rpc_request("edit", &json!({ "method": "cut", "view_id": view_id}));
-    if cut_res.is_err() {
-        error!("failed to cut the selection: {:?}", cut_res);
+pub fn jump_forward(
+    view_id: &str,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::JumpForward {
+        view_id: view_id.to_string(),
     }
+    .send(front_event_writer)?;
+    Ok(Response::Continue)
+}
 
-    let mut buffer = PASTE_BUFFER.lock().unwrap();
-    *buffer = Some(String::from(cut_res.unwrap().as_str().unwrap()));
+pub fn move_up_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveUpAndModifySelection, core);
+    Ok(Response::Continue)
+}
 
-    // Remove the selection
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "collapse_selections", "view_id": view_id}),
+pub fn move_down_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveDownAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn move_left_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveLeftAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn move_right_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveRightAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn move_word_right_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveWordRightAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn move_word_left_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveWordLeftAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn goto_start_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveToBeginningOfDocumentAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn goto_end_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveToEndOfDocumentAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn line_start_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveToLeftEndOfLineAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn line_end_and_select(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::MoveToRightEndOfLineAndModifySelection, core);
+    Ok(Response::Continue)
+}
+
+pub fn insert_newline(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::InsertNewline, core);
+    Ok(Response::Continue)
+}
+
+pub fn add_cursor_above(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::AddSelectionAbove, core);
+    Ok(Response::Continue)
+}
+
+pub fn add_cursor_below(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::AddSelectionBelow, core);
+    Ok(Response::Continue)
+}
+
+pub fn select_next_occurrence(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    // Search for the word under the cursor, or the text of the last
+    // selection, and add its next occurrence to the selections.
+    edit(view_id, EditMethod::SelectionForFind { case_sensitive: true }, core);
+    edit(
+        view_id,
+        EditMethod::FindNext {
+            wrap_around: true,
+            allow_same: false,
+            modify_selection: SelectionModifier::Add,
+        },
+        core,
     );
 
-    Response::SwitchToNormalMode
+    Ok(Response::SwitchToVisualMode)
 }
 
-pub fn cute_selection_and_paste(view_id: &str, core: &dyn Peer) -> Response {
-    let cut_res = core.send_rpc_request("edit", &json!({ "method": "cut", "view_id": view_id}));
-    if cut_res.is_err() {
-        error!("failed to cut the selection: {:?}", cut_res);
+pub fn split_selection_into_lines(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::SelectionIntoLines, core);
+    Ok(Response::Continue)
+}
+
+pub fn collapse_selections(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    edit(view_id, EditMethod::CollapseSelections, core);
+    Ok(Response::Continue)
+}
+
+/// Puts the text returned by a `copy` or `cut` request in the paste buffer.
+/// xi-core returns `null` when nothing is selected, the buffer is kept.
+fn fill_paste_buffer(request: &str, res: Result<Value, xi_rpc::Error>) -> Result<()> {
+    let text = res.map_err(|err| Error::core(request, err))?;
+    if let Some(text) = text.as_str() {
+        *PASTE_BUFFER.lock().unwrap() = Some(text.to_string());
     }
+    Ok(())
+}
+
+pub fn yank_selection(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    let copy_res = CoreRequest::edit(view_id, EditMethod::Copy).send(core);
 
-    paste(view_id, core);
+    // Remove the selection, even if the copy failed.
+    edit(view_id, EditMethod::CollapseSelections, core);
+
+    fill_paste_buffer("copy the selection", copy_res)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn cute_selection(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    let cut_res = CoreRequest::edit(view_id, EditMethod::Cut).send(core);
+
+    // Remove the selection, even if the cut failed.
+    edit(view_id, EditMethod::CollapseSelections, core);
+
+    fill_paste_buffer("cut the selection", cut_res)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn change_selection(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    cute_selection(view_id, core)?;
+
+    Ok(Response::SwitchToInsertMode)
+}
 
-    let mut buffer = PASTE_BUFFER.lock().unwrap();
-    *buffer = Some(String::from(cut_res.unwrap().as_str().unwrap()));
+pub fn cute_selection_and_paste(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    let cut_res = CoreRequest::edit(view_id, EditMethod::Cut).send(core);
+
+    // The selection is replaced by the previous content of the buffer.
+    paste(view_id, core)?;
 
     // Remove the selection
-    core.send_rpc_notification(
-        "edit",
-        &json!({ "method": "collapse_selections", "view_id": view_id}),
-    );
+    edit(view_id, EditMethod::CollapseSelections, core);
 
-    Response::SwitchToNormalMode
+    fill_paste_buffer("cut the selection", cut_res)?;
+    Ok(Response::SwitchToNormalMode)
 }
 
-pub fn paste(view_id: &str, core: &dyn Peer) -> Response {
+pub fn paste(view_id: &str, core: &dyn Peer) -> Result<Response> {
     let buffer = PASTE_BUFFER.lock().unwrap();
     if let Some(ref s) = *buffer {
-        core.send_rpc_notification(
-            "edit",
-            &json!({
-                "method": "paste",
-                "view_id": view_id,
-                "params": {
-                    "chars": s,
-                }
-            }),
-        );
+        edit(view_id, EditMethod::Paste { chars: s.clone() }, core);
     }
 
-    Response::Continue
+    Ok(Response::Continue)
+}
+
+pub fn insert_line_below(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    move_down(view_id, core)?;
+    insert_newline(view_id, core)?;
+    move_up(view_id, core)?;
+
+    Ok(Response::SwitchToInsertMode)
+}
+
+pub fn insert_line_above(view_id: &str, core: &dyn Peer) -> Result<Response> {
+    insert_newline(view_id, core)?;
+    move_up(view_id, core)?;
+
+    Ok(Response::SwitchToInsertMode)
 }
 
-pub fn insert_line_below(view_id: &str, core: &dyn Peer) -> Response {
-    move_down(view_id, core);
-    insert_newline(view_id, core);
-    move_up(view_id, core);
+pub fn toggle_line_numbers(front_event_writer: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::ToggleLineNumbers {}.send(front_event_writer)?;
+    Ok(Response::Continue)
+}
 
-    Response::SwitchToInsertMode
+pub fn toggle_soft_wrap(front_event_writer: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::ToggleSoftWrap {}.send(front_event_writer)?;
+    Ok(Response::Continue)
 }
 
-pub fn insert_line_above(view_id: &str, core: &dyn Peer) -> Response {
-    insert_newline(view_id, core);
-    move_up(view_id, core);
+pub fn plugin_list(
+    view_id: &str,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::ListPlugins {
+        view_id: view_id.to_string(),
+    }
+    .send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
+}
 
-    Response::SwitchToInsertMode
+pub fn plugin_start(view_id: &str, name: &str, core: &dyn Peer) -> Result<Response> {
+    CoreNotification::Plugin(PluginCommand::Start {
+        view_id: view_id.to_string(),
+        plugin_name: name.to_string(),
+    })
+    .send(core);
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn plugin_stop(view_id: &str, name: &str, core: &dyn Peer) -> Result<Response> {
+    CoreNotification::Plugin(PluginCommand::Stop {
+        view_id: view_id.to_string(),
+        plugin_name: name.to_string(),
+    })
+    .send(core);
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn set_language(view_id: &str, language: &str, core: &dyn Peer) -> Result<Response> {
+    CoreNotification::SetLanguage {
+        view_id: view_id.to_string(),
+        language_id: language.to_string(),
+    }
+    .send(core);
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn select_theme(
+    theme: &str,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::SelectTheme {
+        theme: theme.to_string(),
+    }
+    .send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn make_session(
+    path: Option<&str>,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::MakeSession {
+        path: path.map(str::to_string),
+    }
+    .send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn modify_user_config(
+    domain: ConfigDomain,
+    key: &str,
+    value: &Value,
+    core: &dyn Peer,
+) -> Result<Response> {
+    CoreNotification::ModifyUserConfig {
+        domain,
+        changes: json!({ key: value }),
+    }
+    .send(core);
+    Ok(Response::SwitchToNormalMode)
+}
+
+/// Shows a setting of the view `view_id`, or of the views using `language`
+/// if given.
+pub fn show_setting(
+    view_id: &str,
+    language: Option<&str>,
+    key: &str,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::ShowSetting {
+        view_id: view_id.to_string(),
+        language: language.map(str::to_string),
+        key: key.to_string(),
+    }
+    .send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn open_picker(
+    view_id: &str,
+    kind: PickerKind,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    ClientNotification::OpenPicker {
+        view_id: view_id.to_string(),
+        kind,
+    }
+    .send(front_event_writer)?;
+    Ok(Response::SwitchToPickerMode)
+}
+
+pub fn picker_move(delta: i64, front_event_writer: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::PickerMove { delta }.send(front_event_writer)?;
+    Ok(Response::Continue)
+}
+
+pub fn picker_select(front_event_writer: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::PickerSelect {}.send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+pub fn picker_cancel(front_event_writer: &mut ClientToClientWriter) -> Result<Response> {
+    ClientNotification::PickerCancel {}.send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
+}
+
+/// Changes the filter of the logs to `directives`, like `debug,xi_rpc=warn`,
+/// or shows the current one.
+pub fn log_filter(
+    directives: Option<&str>,
+    front_event_writer: &mut ClientToClientWriter,
+) -> Result<Response> {
+    let (severity, message) = match directives.map(str::parse::<Filter>) {
+        Some(Ok(filter)) => {
+            info!("logging to level {}", filter);
+            let message = format!("log level: {}", filter);
+            logging::set_filter(filter);
+            (Severity::Info, message)
+        }
+        Some(Err(err)) => (Severity::Error, err),
+        None => (Severity::Info, format!("log level: {}", logging::filter())),
+    };
+
+    ClientNotification::ShowMessage {
+        message,
+        severity,
+        sticky: false,
+    }
+    .send(front_event_writer)?;
+    Ok(Response::SwitchToNormalMode)
 }
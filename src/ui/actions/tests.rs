@@ -0,0 +1,341 @@
+use std::io::BufRead;
+use std::sync::Mutex;
+
+use serde_json::Value;
+use xi_rpc::RemoteError;
+
+use super::{Action, Response};
+use crate::channel::{self, Reader};
+use crate::core::ClientToClientWriter;
+use crate::error::Result;
+use crate::protocol::{
+    ClientNotification, CoreNotification, CoreRequest, EditMethod, SelectionModifier,
+};
+use crate::tests::MockPeer;
+use crate::ui::keyboard::KeyStroke;
+use crate::ui::PASTE_BUFFER;
+
+const VIEW_ID: &str = "view-id-1";
+
+/// Serializes the tests using the paste buffer, shared by all the tests.
+static PASTE_BUFFER_TESTS: Mutex<()> = Mutex::new(());
+
+/// Executes an action against a `MockPeer`, recording the notifications
+/// sent to the EventController.
+struct Context {
+    core: MockPeer,
+    front_event_writer: ClientToClientWriter,
+    front_event_reader: Reader,
+}
+
+impl Context {
+    fn new() -> Self {
+        let (writer, reader) = channel::channel();
+        Context {
+            core: MockPeer::new(),
+            front_event_writer: ClientToClientWriter::new(writer),
+            front_event_reader: reader,
+        }
+    }
+
+    fn execute(&mut self, action: Action) -> Response {
+        self.try_execute(action)
+            .unwrap_or_else(|err| panic!("{:?} failed: {}", action, err))
+    }
+
+    fn try_execute(&mut self, action: Action) -> Result<Response> {
+        action.execute(VIEW_ID, &self.core, &mut self.front_event_writer)
+    }
+
+    fn edits(&self) -> Vec<EditMethod> {
+        self.core
+            .notifications()
+            .into_iter()
+            .map(|notification| match notification {
+                CoreNotification::Edit(edit) => {
+                    assert_eq!(edit.view_id, VIEW_ID);
+                    edit.method
+                }
+                notification => panic!("not an edit: {:?}", notification),
+            })
+            .collect()
+    }
+
+    /// Returns the notifications sent to the EventController.
+    fn client_notifications(self) -> Vec<ClientNotification> {
+        // The reader stops once the writer is dropped.
+        drop(self.front_event_writer);
+        self.front_event_reader
+            .lines()
+            .map(|line| serde_json::from_str(&line.unwrap()).unwrap())
+            .collect()
+    }
+}
+
+fn edit_request(method: EditMethod) -> CoreRequest {
+    CoreRequest::edit(VIEW_ID, method)
+}
+
+fn remote_error() -> RemoteError {
+    RemoteError::custom(-32000, "no selection", None)
+}
+
+#[test]
+fn moves() {
+    let moves = vec![
+        (Action::MoveUp, EditMethod::MoveUp),
+        (Action::MoveDown, EditMethod::MoveDown),
+        (Action::MoveLeft, EditMethod::MoveLeft),
+        (Action::MoveRight, EditMethod::MoveRight),
+        (Action::MoveWordLeft, EditMethod::MoveWordLeft),
+        (Action::MoveWordRight, EditMethod::MoveWordRight),
+        (Action::PageUp, EditMethod::ScrollPageUp),
+        (Action::PageDown, EditMethod::ScrollPageDown),
+        (Action::LineStart, EditMethod::MoveToLeftEndOfLine),
+        (Action::LineEnd, EditMethod::MoveToRightEndOfLine),
+        (Action::MoveUpAndSelect, EditMethod::MoveUpAndModifySelection),
+        (Action::MoveDownAndSelect, EditMethod::MoveDownAndModifySelection),
+        (Action::MoveLeftAndSelect, EditMethod::MoveLeftAndModifySelection),
+        (Action::MoveRightAndSelect, EditMethod::MoveRightAndModifySelection),
+        (Action::MoveWordLeftAndSelect, EditMethod::MoveWordLeftAndModifySelection),
+        (Action::MoveWordRightAndSelect, EditMethod::MoveWordRightAndModifySelection),
+        (Action::GotoStartAndSelect, EditMethod::MoveToBeginningOfDocumentAndModifySelection),
+        (Action::GotoEndAndSelect, EditMethod::MoveToEndOfDocumentAndModifySelection),
+        (Action::LineStartAndSelect, EditMethod::MoveToLeftEndOfLineAndModifySelection),
+        (Action::LineEndAndSelect, EditMethod::MoveToRightEndOfLineAndModifySelection),
+    ];
+
+    for (action, method) in moves {
+        let mut context = Context::new();
+        assert_eq!(context.execute(action), Response::Continue, "{:?}", action);
+        assert_eq!(context.edits(), vec![method], "{:?}", action);
+        assert!(context.core.requests().is_empty());
+        assert!(context.client_notifications().is_empty());
+    }
+}
+
+#[test]
+fn mode_switches() {
+    let switches = vec![
+        (Action::SwitchToInsertMode, Response::SwitchToInsertMode),
+        (Action::SwitchToVisualMode, Response::SwitchToVisualMode),
+        (Action::SwitchToActionMode, Response::SwitchToActionMode),
+        (Action::SwitchToNormalMode, Response::SwitchToNormalMode),
+        (Action::RepeatLastChange, Response::RepeatLastChange),
+        (Action::ToggleMacroRecording, Response::ToggleMacroRecording),
+        (Action::PlayMacro, Response::PlayMacro),
+        (Action::Quite, Response::Stop),
+    ];
+
+    for (action, response) in switches {
+        let mut context = Context::new();
+        assert_eq!(context.execute(action), response, "{:?}", action);
+        assert!(context.core.notifications().is_empty());
+        assert!(context.client_notifications().is_empty());
+    }
+}
+
+#[test]
+fn insert_keystroke() {
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::InsertKeyStroke(KeyStroke::Char('a'))), Response::Continue);
+    assert_eq!(context.execute(Action::InsertKeyStroke(KeyStroke::KeySpace)), Response::Continue);
+
+    assert_eq!(
+        context.edits(),
+        vec![
+            EditMethod::Insert {
+                chars: String::from("a")
+            },
+            EditMethod::Insert {
+                chars: String::from(" ")
+            },
+        ]
+    );
+}
+
+#[test]
+fn insert_lines() {
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::InsertLineBelow), Response::SwitchToInsertMode);
+    assert_eq!(
+        context.edits(),
+        vec![EditMethod::MoveDown, EditMethod::InsertNewline, EditMethod::MoveUp]
+    );
+
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::InsertLineAbove), Response::SwitchToInsertMode);
+    assert_eq!(context.edits(), vec![EditMethod::InsertNewline, EditMethod::MoveUp]);
+}
+
+#[test]
+fn goto_pushes_a_jump() {
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::GotoLine(12)), Response::Continue);
+    assert_eq!(context.execute(Action::GotoStart), Response::Continue);
+
+    assert_eq!(
+        context.edits(),
+        vec![EditMethod::GotoLine { line: 11 }, EditMethod::MoveToBeginningOfDocument]
+    );
+    let jump = ClientNotification::PushJump {
+        view_id: VIEW_ID.to_string(),
+    };
+    assert_eq!(context.client_notifications(), vec![jump.clone(), jump]);
+}
+
+#[test]
+fn select_next_occurrence() {
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::SelectNextOccurrence), Response::SwitchToVisualMode);
+    assert_eq!(
+        context.edits(),
+        vec![
+            EditMethod::SelectionForFind {
+                case_sensitive: true
+            },
+            EditMethod::FindNext {
+                wrap_around: true,
+                allow_same: false,
+                modify_selection: SelectionModifier::Add,
+            },
+        ]
+    );
+}
+
+#[test]
+fn frontend_actions() {
+    let actions = vec![
+        (Action::WriteToFile, Response::SwitchToNormalMode),
+        (Action::ToggleLineNumbers, Response::Continue),
+        (Action::PickerNext, Response::Continue),
+    ];
+    let notifications = vec![
+        ClientNotification::WriteToFile {
+            view_id: VIEW_ID.to_string(),
+        },
+        ClientNotification::ToggleLineNumbers {},
+        ClientNotification::PickerMove { delta: 1 },
+    ];
+
+    let mut context = Context::new();
+    for (action, response) in actions {
+        assert_eq!(context.execute(action), response, "{:?}", action);
+    }
+    assert!(context.core.notifications().is_empty());
+    assert_eq!(context.client_notifications(), notifications);
+}
+
+#[test]
+fn yank_selection() {
+    let _guard = PASTE_BUFFER_TESTS.lock().unwrap();
+    *PASTE_BUFFER.lock().unwrap() = None;
+
+    let mut context = Context::new();
+    context.core.respond(edit_request(EditMethod::Copy), Ok(Value::from("text")));
+    assert_eq!(context.execute(Action::YankSelection), Response::SwitchToNormalMode);
+
+    assert_eq!(context.core.requests(), vec![edit_request(EditMethod::Copy)]);
+    assert_eq!(context.edits(), vec![EditMethod::CollapseSelections]);
+    assert_eq!(*PASTE_BUFFER.lock().unwrap(), Some(String::from("text")));
+}
+
+#[test]
+fn yank_selection_error() {
+    let _guard = PASTE_BUFFER_TESTS.lock().unwrap();
+    *PASTE_BUFFER.lock().unwrap() = Some(String::from("previous"));
+
+    let mut context = Context::new();
+    context.core.respond(edit_request(EditMethod::Copy), Err(remote_error()));
+    let err = context.try_execute(Action::YankSelection).unwrap_err();
+    assert_eq!(err.to_string(), "failed to copy the selection: no selection");
+
+    assert_eq!(context.edits(), vec![EditMethod::CollapseSelections]);
+    assert_eq!(*PASTE_BUFFER.lock().unwrap(), Some(String::from("previous")));
+}
+
+#[test]
+fn delete_selection() {
+    let _guard = PASTE_BUFFER_TESTS.lock().unwrap();
+    *PASTE_BUFFER.lock().unwrap() = None;
+
+    let mut context = Context::new();
+    context.core.respond(edit_request(EditMethod::Cut), Ok(Value::from("text")));
+    assert_eq!(context.execute(Action::DeleteSelection), Response::SwitchToNormalMode);
+    assert_eq!(context.execute(Action::ChangeSelection), Response::SwitchToInsertMode);
+
+    assert_eq!(
+        context.core.requests(),
+        vec![edit_request(EditMethod::Cut), edit_request(EditMethod::Cut)]
+    );
+    assert_eq!(
+        context.edits(),
+        vec![EditMethod::CollapseSelections, EditMethod::CollapseSelections]
+    );
+    assert_eq!(*PASTE_BUFFER.lock().unwrap(), Some(String::from("text")));
+}
+
+#[test]
+fn delete_selection_error() {
+    let _guard = PASTE_BUFFER_TESTS.lock().unwrap();
+    *PASTE_BUFFER.lock().unwrap() = Some(String::from("previous"));
+
+    let mut context = Context::new();
+    context.core.respond(edit_request(EditMethod::Cut), Err(remote_error()));
+    let err = context.try_execute(Action::DeleteSelection).unwrap_err();
+    assert_eq!(err.to_string(), "failed to cut the selection: no selection");
+
+    assert_eq!(context.edits(), vec![EditMethod::CollapseSelections]);
+    assert_eq!(*PASTE_BUFFER.lock().unwrap(), Some(String::from("previous")));
+}
+
+#[test]
+fn delete_selection_and_paste() {
+    let _guard = PASTE_BUFFER_TESTS.lock().unwrap();
+    *PASTE_BUFFER.lock().unwrap() = Some(String::from("previous"));
+
+    let mut context = Context::new();
+    context.core.respond(edit_request(EditMethod::Cut), Ok(Value::from("text")));
+    assert_eq!(context.execute(Action::DeleteSelectionAndPaste), Response::SwitchToNormalMode);
+
+    assert_eq!(
+        context.edits(),
+        vec![
+            EditMethod::Paste {
+                chars: String::from("previous")
+            },
+            EditMethod::CollapseSelections,
+        ]
+    );
+    assert_eq!(*PASTE_BUFFER.lock().unwrap(), Some(String::from("text")));
+}
+
+#[test]
+fn paste() {
+    let _guard = PASTE_BUFFER_TESTS.lock().unwrap();
+
+    *PASTE_BUFFER.lock().unwrap() = None;
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::Paste), Response::Continue);
+    assert!(context.edits().is_empty());
+
+    *PASTE_BUFFER.lock().unwrap() = Some(String::from("text"));
+    let mut context = Context::new();
+    assert_eq!(context.execute(Action::Paste), Response::Continue);
+    assert_eq!(
+        context.edits(),
+        vec![EditMethod::Paste {
+            chars: String::from("text")
+        }]
+    );
+}
+
+#[test]
+fn event_controller_stopped() {
+    let (writer, reader) = channel::channel();
+    let mut front_event_writer = ClientToClientWriter::new(writer);
+    drop(reader);
+
+    let res = Action::WriteToFile.execute(VIEW_ID, &MockPeer::new(), &mut front_event_writer);
+    assert!(res.is_err());
+}
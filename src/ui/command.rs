@@ -0,0 +1,222 @@
+use super::actions::{rpc, Response};
+use crate::core::ClientToClientWriter;
+use crate::error::Result;
+use crate::protocol::ConfigDomain;
+use crate::ui::picker::PickerKind;
+
+use serde_json::Value;
+use xi_rpc::Peer;
+
+/// The command names completed on the command line.
+const COMMANDS: &[&str] = &[
+    "detach", "log", "messages", "mksession", "plugin", "quit", "set", "theme", "write",
+];
+
+/// The views whose settings are changed by a `:set` command.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Scope {
+    /// The settings of every view.
+    General,
+    /// The settings of the views using a language.
+    Syntax(String),
+    /// The settings of the current view only.
+    Buffer,
+}
+
+impl Scope {
+    /// Returns the language of the `Syntax` scope.
+    fn language(&self) -> Option<&str> {
+        match self {
+            Scope::Syntax(language) => Some(language),
+            _ => None,
+        }
+    }
+
+    fn to_domain(&self, view_id: &str) -> ConfigDomain {
+        match self {
+            Scope::General => ConfigDomain::General,
+            Scope::Syntax(language) => ConfigDomain::Syntax(language.clone()),
+            Scope::Buffer => ConfigDomain::UserOverride(view_id.to_string()),
+        }
+    }
+}
+
+/// A command typed on the command line in the action mode, like `:w`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub enum Command {
+    WriteToFile,
+    Quite,
+    /// Detaches from the session server, see `server`.
+    Detach,
+    /// Saves the open views to a session file, the default one if no path
+    /// is given.
+    MakeSession(Option<String>),
+    /// Lists the messages shown on the status bar so far.
+    Messages,
+
+    PluginList,
+    PluginStart(String),
+    PluginStop(String),
+
+    /// Opens the picker of the languages when no language is given.
+    SetLanguage(Option<String>),
+    /// Opens the picker of the themes when no theme is given.
+    Theme(Option<String>),
+
+    /// Changes the setting `key`.
+    Set {
+        scope: Scope,
+        key: String,
+        value: Value,
+    },
+    /// Shows the value of a setting in the scope.
+    ShowSetting { scope: Scope, key: String },
+
+    /// Changes the log level with directives like `debug,xi_rpc=warn`, or
+    /// shows it if none is given.
+    Log(Option<String>),
+}
+
+impl Command {
+    pub fn execute(
+        self,
+        view_id: &str,
+        core: &dyn Peer,
+        front_event_writer: &mut ClientToClientWriter,
+    ) -> Result<Response> {
+        match self {
+            Command::WriteToFile => rpc::write_to_file(view_id, front_event_writer),
+            Command::Quite => rpc::quite(),
+            Command::Detach => rpc::detach(),
+            Command::MakeSession(path) => rpc::make_session(path.as_deref(), front_event_writer),
+            Command::Messages => rpc::show_messages(),
+
+            Command::PluginList => rpc::plugin_list(view_id, front_event_writer),
+            Command::PluginStart(name) => rpc::plugin_start(view_id, &name, core),
+            Command::PluginStop(name) => rpc::plugin_stop(view_id, &name, core),
+
+            Command::SetLanguage(Some(language)) => rpc::set_language(view_id, &language, core),
+            Command::SetLanguage(None) => {
+                rpc::open_picker(view_id, PickerKind::Language, front_event_writer)
+            }
+
+            Command::Theme(Some(theme)) => rpc::select_theme(&theme, front_event_writer),
+            Command::Theme(None) => {
+                rpc::open_picker(view_id, PickerKind::Theme, front_event_writer)
+            }
+
+            Command::Set { scope, key, value } => {
+                rpc::modify_user_config(scope.to_domain(view_id), &key, &value, core)
+            }
+            Command::ShowSetting { scope, key } => {
+                rpc::show_setting(view_id, scope.language(), &key, front_event_writer)
+            }
+
+            Command::Log(directives) => rpc::log_filter(directives.as_deref(), front_event_writer),
+        }
+    }
+
+    /// Parses a command line, returns the message shown to the user if it
+    /// isn't a valid command.
+    pub fn from_description(desc: &str) -> Result<Command, String> {
+        let words: Vec<&str> = desc.split_whitespace().collect();
+
+        let command = match words.as_slice() {
+            ["w"] | ["write"] => Some(Command::WriteToFile),
+            ["q"] | ["quit"] => Some(Command::Quite),
+            ["detach"] => Some(Command::Detach),
+            ["mksession"] => Some(Command::MakeSession(None)),
+            ["mksession", path] => Some(Command::MakeSession(Some(path.to_string()))),
+            ["mes"] | ["messages"] => Some(Command::Messages),
+
+            ["plugin", "list"] => Some(Command::PluginList),
+            ["plugin", "start", name] => Some(Command::PluginStart(name.to_string())),
+            ["plugin", "stop", name] => Some(Command::PluginStop(name.to_string())),
+
+            // Some language names have spaces, like "Plain Text".
+            ["set", "language"] => Some(Command::SetLanguage(None)),
+            ["set", "language", language @ ..] => {
+                Some(Command::SetLanguage(Some(language.join(" "))))
+            }
+
+            ["theme"] => Some(Command::Theme(None)),
+            ["theme", theme @ ..] => Some(Command::Theme(Some(theme.join(" ")))),
+
+            ["set"] | ["set", "general"] | ["set", "syntax", _] => {
+                return Err(String::from("missing setting name"));
+            }
+            ["set", "syntax"] => return Err(String::from("missing language")),
+            ["set", "general", key] => Some(Command::show_setting(Scope::General, key)),
+            ["set", "general", key, value @ ..] => Some(Command::set(Scope::General, key, value)),
+            ["set", "syntax", language, key] => {
+                Some(Command::show_setting(Scope::Syntax(language.to_string()), key))
+            }
+            ["set", "syntax", language, key, value @ ..] => {
+                Some(Command::set(Scope::Syntax(language.to_string()), key, value))
+            }
+            ["set", key] => Some(Command::show_setting(Scope::Buffer, key)),
+            ["set", key, value @ ..] => Some(Command::set(Scope::Buffer, key, value)),
+
+            ["log"] => Some(Command::Log(None)),
+            ["log", directives @ ..] => Some(Command::Log(Some(directives.join(",")))),
+
+            _ => None,
+        };
+
+        command.ok_or_else(|| format!("unknown command: {}", desc))
+    }
+
+    fn show_setting(scope: Scope, key: &str) -> Command {
+        Command::ShowSetting {
+            scope,
+            key: key.to_string(),
+        }
+    }
+
+    /// Returns the `Set` command of a non-empty value.
+    ///
+    /// The value is parsed as JSON, `4` being a number and `true` a boolean,
+    /// or is taken as a string if it isn't valid JSON.
+    fn set(scope: Scope, key: &str, value: &[&str]) -> Command {
+        let value = value.join(" ");
+        Command::Set {
+            scope,
+            key: key.to_string(),
+            value: serde_json::from_str(&value).unwrap_or(Value::String(value)),
+        }
+    }
+}
+
+/// Completes the command name or the theme being typed on the command line,
+/// up to the longest text shared by every candidate.
+///
+/// Returns `None` if there is nothing to complete.
+pub fn complete(command_line: &str, themes: &[String]) -> Option<String> {
+    let theme = command_line.strip_prefix("theme ");
+    let (typed, candidates): (&str, Vec<&str>) = if let Some(theme) = theme {
+        (theme.trim_start(), themes.iter().map(String::as_str).collect())
+    } else if !command_line.contains(' ') {
+        (command_line, COMMANDS.to_vec())
+    } else {
+        return None;
+    };
+
+    let mut matching = candidates.iter().filter(|candidate| candidate.starts_with(typed));
+    let first = matching.next()?;
+    let shared = matching.fold(*first, |shared, candidate| {
+        let len = shared
+            .char_indices()
+            .zip(candidate.chars())
+            .take_while(|((_, a), b)| a == b)
+            .last()
+            .map_or(0, |((idx, c), _)| idx + c.len_utf8());
+        &shared[..len]
+    });
+
+    if shared.len() <= typed.len() {
+        return None;
+    }
+
+    let prefix = &command_line[..command_line.len() - typed.len()];
+    Some(format!("{}{}", prefix, shared))
+}
@@ -1,13 +1,72 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::mpsc::Receiver;
+
 use crate::core::ClientToClientWriter;
-use super::keyboard::Keyboard;
-use super::actions::Action;
+use crate::error::{Error, Result};
+use crate::protocol::{
+    ClientNotification, CoreNotification, CoreRequest, EditMethod, SessionView, StatusItem,
+};
+use crate::session::Session;
+use crate::swap::{self, SwapDir, SwapFile};
+use super::status_bar::Alignment;
+use super::keyboard::{KeyStroke, Keyboard, ReplayKeyboard};
+use super::actions::{Action, Response};
+use super::command::{self, Command};
+use super::keymap::{Binding, KeyMap};
+use super::macros::MacroRegisters;
+use super::messages::Severity;
+use super::repeat::ChangeRecorder;
+use super::Mode;
 use xi_rpc::Peer;
 
+/// Limit of nested macro playbacks, a macro calling itself would never end
+/// otherwise.
+const MAX_MACRO_DEPTH: usize = 16;
+
+/// A command waiting for the name of the register it applies to, like `q`
+/// in `qa`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+enum RegisterCommand {
+    Record,
+    Play(usize),
+}
 
 pub struct InputController {
     keyboard: Box<dyn Keyboard>,
+    keymap: KeyMap,
     view_id: String,
+    /// The path of the view, `None` for a new file.
+    view_path: Option<String>,
+    mode: Mode,
     front_event_writer: ClientToClientWriter,
+
+    /// Keys typed so far for a binding made of several keys.
+    pending_keys: Vec<KeyStroke>,
+    /// Count typed before a normal mode command, like `3` in `3x`.
+    count: Option<usize>,
+    /// Operator waiting for its motion with its count, like `d` in `dw`.
+    operator: Option<(Action, usize)>,
+    changes: ChangeRecorder,
+    /// The command typed in the action mode.
+    command_line: String,
+    /// The themes completed on the command line, received from the
+    /// EventController.
+    themes: Vec<String>,
+    themes_receiver: Option<Receiver<Vec<String>>>,
+
+    macros: MacroRegisters,
+    register_command: Option<RegisterCommand>,
+    last_played_register: Option<char>,
+    macro_depth: usize,
+
+    /// Attached to a session server, whose views are left open on detach.
+    attached: bool,
+    detached: bool,
+
+    /// Where the swap files of the opened files are looked for.
+    swap: Option<SwapDir>,
 }
 
 impl InputController {
@@ -17,82 +76,742 @@ impl InputController {
     ) -> Self {
         Self {
             keyboard,
+            keymap: KeyMap::default(),
             view_id: String::new(),
+            view_path: None,
+            mode: Mode::Normal,
             front_event_writer: client_to_client_writer,
+            pending_keys: Vec::new(),
+            count: None,
+            operator: None,
+            changes: ChangeRecorder::new(),
+            command_line: String::new(),
+            themes: Vec::new(),
+            themes_receiver: None,
+            macros: MacroRegisters::new(),
+            register_command: None,
+            last_played_register: None,
+            macro_depth: 0,
+            attached: false,
+            detached: false,
+            swap: None,
+        }
+    }
+
+    /// Offers to recover the changes left in `swap_dir` by a previous
+    /// frontend when a file is opened.
+    pub fn use_swap_dir(&mut self, swap_dir: SwapDir) {
+        self.swap = Some(swap_dir);
+    }
+
+    /// Completes the themes received from `themes_receiver`.
+    pub fn receive_themes_from(&mut self, themes_receiver: Receiver<Vec<String>>) {
+        self.themes_receiver = Some(themes_receiver);
+    }
+
+    /// Loads the macros saved in `file_path` and saves the new ones in it.
+    pub fn persist_macros(&mut self, file_path: &Path) -> io::Result<()> {
+        self.macros.persist_to(file_path)
+    }
+
+    pub fn open_file(&mut self, core: &dyn Peer, file_path: &str) -> Result<()> {
+        let request = format!("open {}", file_path);
+        let view_id = CoreRequest::NewView {
+            file_path: Some(file_path.to_string()),
+        }
+        .send(core)
+        .map_err(|err| Error::core(request.as_str(), err))?;
+
+        match view_id.as_str() {
+            Some(view_id) => self.show_view(view_id, Some(file_path.to_string()))?,
+            None => return Err(Error::invalid_response(request, view_id)),
         }
+
+        self.recover_swap_file(core, file_path)
     }
 
-    pub fn open_file(&mut self, core: &dyn Peer, file_path: &str) -> Result<(), Error> {
-        let view_id = core
-            .send_rpc_request("new_view", &json!({ "file_path": file_path }))
-            .expect("failed to create the new view");
+    /// Asks the user whether to restore the changes found in the swap file
+    /// of `file_path`, after showing their diff if asked to.
+    fn recover_swap_file(&mut self, core: &dyn Peer, file_path: &str) -> Result<()> {
+        let swap = match self.swap {
+            Some(ref swap) => swap.clone(),
+            None => return Ok(()),
+        };
+        let swap_file = match swap.read(file_path) {
+            Ok(Some(swap_file)) => swap_file,
+            Ok(None) => return Ok(()),
+            Err(err) => {
+                error!("failed to read the swap file of {}: {}", file_path, err);
+                return Ok(());
+            }
+        };
+
+        if swap_file.is_live() {
+            return self.show_message(format!(
+                "{} is also edited by the process {}",
+                file_path, swap_file.pid
+            ));
+        }
 
-        self.view_id = view_id.as_str().unwrap().to_string();
+        let saved = fs::read_to_string(file_path).unwrap_or_default();
+        if swap_file.contents == saved {
+            // Nothing was lost.
+            return swap.remove(file_path).map_err(Error::from);
+        }
 
-        self.front_event_writer.send_rpc_notification(
-            "set_path_for_view",
-            &json!({
-                "view_id": self.view_id,
-                "path": file_path,
-            }),
+        let view_id = self.view_id.clone();
+        let prompt = format!(
+            "{} has unsaved changes from {}: r restore, d diff, x discard, <esc> later",
+            file_path, swap_file.time
         );
+        loop {
+            self.show_prompt(prompt.clone())?;
 
-        core.send_rpc_notification("set_theme", &json!({"theme_name": "Solarized (light)" }));
-        self.front_event_writer.send_rpc_notification(
-            "add_status_item",
-            &json!({
-                "key": "change-mode",
-                "value": self.mode.to_string(),
-                "alignment": "left",
-            }),
+            match self.keyboard.get_next_keystroke() {
+                Some(KeyStroke::Char('r')) => {
+                    // The swap file is replaced once the restored text is
+                    // swapped again.
+                    for method in [
+                        EditMethod::SelectAll,
+                        EditMethod::Paste {
+                            chars: swap_file.contents.clone(),
+                        },
+                        EditMethod::MoveToBeginningOfDocument,
+                    ] {
+                        CoreNotification::edit(&view_id, method).send(core);
+                    }
+                    return self.show_message(format!("restored the changes of {}", file_path));
+                }
+                Some(KeyStroke::Char('d')) => self.show_swap_diff(core, &swap_file, &saved)?,
+                Some(KeyStroke::Char('x')) => {
+                    swap.remove(file_path)?;
+                    return self.show_message(format!("discarded the changes of {}", file_path));
+                }
+                Some(KeyStroke::KeyEscape) | None => {
+                    return self.show_message(String::new());
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// Shows the changes of the swap file until a key is typed.
+    fn show_swap_diff(
+        &mut self,
+        core: &dyn Peer,
+        swap_file: &SwapFile,
+        saved: &str,
+    ) -> Result<()> {
+        let diff = format!(
+            "--- {} (saved)\n+++ {} (swap file)\n{}",
+            swap_file.path,
+            swap_file.path,
+            swap::diff(saved, &swap_file.contents)
         );
+        self.show_text(core, "show the changes", diff)
+    }
+
+    /// Lets the EventController list the messages shown so far until a key
+    /// is typed.
+    fn show_messages(&mut self) -> Result<()> {
+        ClientNotification::ShowMessages {}.send(&mut self.front_event_writer)?;
+        self.keyboard.get_next_keystroke();
+        ClientNotification::HideMessages {}.send(&mut self.front_event_writer)
+    }
+
+    /// Shows `text` in a new view until a key is typed, then closes it and
+    /// goes back to the current view.
+    fn show_text(&mut self, core: &dyn Peer, request: &str, text: String) -> Result<()> {
+        let (current_id, current_path) = (self.view_id.clone(), self.view_path.clone());
+        let view_id = CoreRequest::NewView { file_path: None }
+            .send(core)
+            .map_err(|err| Error::core(request, err))?;
+        let view_id = match view_id.as_str() {
+            Some(view_id) => view_id.to_string(),
+            None => return Err(Error::invalid_response(request, view_id)),
+        };
+        self.show_view(&view_id, None)?;
+
+        CoreNotification::edit(&view_id, EditMethod::Paste { chars: text }).send(core);
+        CoreNotification::edit(&view_id, EditMethod::MoveToBeginningOfDocument).send(core);
 
+        self.show_prompt(String::from("type any key to go back"))?;
+        self.keyboard.get_next_keystroke();
+        self.show_message(String::new())?;
+
+        ClientNotification::CloseView { view_id }.send(&mut self.front_event_writer)?;
+        self.show_view(&current_id, current_path)
+    }
+
+    /// Attaches to a session server, showing the views it kept open. The
+    /// file is opened if the session has no view yet.
+    pub fn attach(&mut self, core: &dyn Peer, file_path: &str) -> Result<()> {
+        let views = CoreRequest::Attach {}
+            .send(core)
+            .map_err(|err| Error::core("attach to the session", err))?;
+        let views: Vec<SessionView> = serde_json::from_value(views)?;
+        self.attached = true;
+
+        if views.is_empty() {
+            return self.open_file(core, file_path);
+        }
+
+        // The EventController shows the last view, the one opened last.
+        for view in views {
+            self.show_view(&view.view_id, view.file_path)?;
+        }
         Ok(())
     }
 
-    pub fn start_keyboard_event_loop(&mut self, core: &dyn Peer) -> Result<(), Error> {
-        loop {
-            let key_res = self.keyboard.get_next_keystroke();
+    /// Reopens the views saved in the session file `session_path`, with
+    /// their positions and the theme. The file is opened if the session has
+    /// no view.
+    pub fn restore_session(
+        &mut self,
+        core: &dyn Peer,
+        session_path: &Path,
+        file_path: &str,
+    ) -> Result<()> {
+        // The session file is only written when the first session ends.
+        let session = match Session::load(session_path) {
+            Ok(session) => session,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => Session::default(),
+            Err(err) => {
+                let message =
+                    format!("failed to load the session {}: {}", session_path.display(), err);
+                return Err(io::Error::new(err.kind(), message).into());
+            }
+        };
+
+        if let Some(theme_name) = session.theme {
+            CoreNotification::SetTheme { theme_name }.send(core);
+        }
+        if session.views.is_empty() {
+            return self.open_file(core, file_path);
+        }
+
+        // The EventController shows the last view, the one displayed when
+        // the session was saved.
+        for view in session.views {
+            self.open_file(core, &view.path)?;
+            ClientNotification::RestoreView {
+                view_id: self.view_id.clone(),
+                line: view.line,
+                col: view.col,
+                first_line: view.first_line,
+                first_col: view.first_col,
+            }
+            .send(&mut self.front_event_writer)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the keyboard read by `start_keyboard_event_loop`.
+    #[cfg(test)]
+    pub fn set_keyboard(&mut self, keyboard: Box<dyn Keyboard>) {
+        self.keyboard = keyboard;
+    }
+
+    #[cfg(test)]
+    pub fn view_id(&self) -> &str {
+        &self.view_id
+    }
+
+    /// Detached from the session server by the user, the views must be
+    /// left open.
+    pub fn detached(&self) -> bool {
+        self.detached
+    }
+
+    fn show_view(&mut self, view_id: &str, path: Option<String>) -> Result<()> {
+        self.view_id = view_id.to_string();
+        self.view_path = path.clone();
+
+        ClientNotification::SetPathForView {
+            view_id: self.view_id.clone(),
+            path,
+        }
+        .send(&mut self.front_event_writer)?;
+
+        ClientNotification::AddStatusItem(StatusItem {
+            key: String::from("change-mode"),
+            value: self.mode.to_string(),
+            alignment: Alignment::Left,
+        })
+        .send(&mut self.front_event_writer)
+    }
+
+    /// Stops with an error once the EventController stopped, the other
+    /// errors are shown to the user.
+    pub fn start_keyboard_event_loop(&mut self, core: &dyn Peer) -> Result<()> {
+        while let Some(key) = self.keyboard.get_next_keystroke() {
+            // Only the keys typed by the user are recorded, not the ones
+            // replayed from a macro.
+            self.macros.record(key);
+
+            let res = match self.handle_keystroke(key, core) {
+                Ok(res) => res,
+                // The EventController stopped since xi-core is gone, after
+                // showing why.
+                Err(Error::Io(ref err)) if err.kind() == io::ErrorKind::BrokenPipe => {
+                    info!("the EventController stopped");
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+
+            match res {
+                Response::Stop => break,
+                Response::Detach => {
+                    self.detached = true;
+                    break;
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Stops the EventController once it handled the pending notifications,
+    /// the saves asked by the user included.
+    pub fn shutdown(&mut self) {
+        self.front_event_writer.shutdown();
+    }
+
+    fn handle_keystroke(&mut self, key: KeyStroke, core: &dyn Peer) -> Result<Response> {
+        if let Some(command) = self.register_command.take() {
+            return self.handle_register_command(command, key, core);
+        }
+
+        if self.mode == Mode::Normal && self.pending_keys.is_empty() {
+            if let KeyStroke::Char(c) = key {
+                match c.to_digit(10) {
+                    // A leading `0` is not a count.
+                    Some(0) if self.count.is_none() => (),
+                    Some(digit) => {
+                        self.count = Some(self.count.unwrap_or(0) * 10 + digit as usize);
+                        return Ok(Response::Continue);
+                    }
+                    None => (),
+                }
+            }
+        }
+
+        self.pending_keys.push(key);
+        let action = match self.keymap.lookup(self.mode, &self.pending_keys) {
+            Binding::Action(action) => action,
+            Binding::Prefix => return Ok(Response::Continue),
+            Binding::Unbound if self.mode == Mode::Insert => Action::InsertKeyStroke(key),
+            Binding::Unbound if self.mode == Mode::Action => {
+                self.pending_keys.clear();
+                return self.edit_command_line(key, core);
+            }
+            Binding::Unbound => {
+                self.count = None;
+                self.operator = None;
+                self.pending_keys.clear();
+                return Ok(Response::Continue);
+            }
+        };
+        self.pending_keys.clear();
+
+        let count = self.count.take();
+        let res = self.dispatch(action, count, core)?;
+
+        if self.mode == Mode::Normal {
+            // Back in normal mode, the change started by the last command
+            // is complete. The text typed after a count is inserted again.
+            let repetitions = self.changes.finish();
+            self.execute_all(&repetitions, core)?;
+        }
+
+        Ok(res)
+    }
+
+    fn dispatch(
+        &mut self,
+        action: Action,
+        count: Option<usize>,
+        core: &dyn Peer,
+    ) -> Result<Response> {
+        // The count can be an argument of the action rather than a number
+        // of repetitions.
+        let (action, count) = match count.and_then(|count| action.with_count(count)) {
+            Some(action) => (action, None),
+            None => (action, count),
+        };
+
+        if self.mode != Mode::Normal {
+            self.changes.record(action);
+            return self.execute(action, count, core);
+        }
 
-            if let Some(key) = key_res {
+        if let Some((operator, operator_count)) = self.operator.take() {
+            let motion = match action.with_selection() {
+                Some(motion) => motion,
+                // Not a motion, the operator is cancelled.
+                None => return Ok(Response::Continue),
+            };
 
-                action = Some(Action::InsertKeyStroke(key));
-                if action.is_none() {
-                    continue;
+            let count = operator_count * count.unwrap_or(1);
+            self.changes.start(count);
+            self.changes.record(motion);
+            self.changes.record(operator);
+
+            self.execute(motion, Some(count), core)?;
+            return self.execute(operator, None, core);
+        }
+
+        if action.is_operator() {
+            self.operator = Some((action, count.unwrap_or(1)));
+            return Ok(Response::Continue);
+        }
+
+        if action.is_change() || action == Action::SwitchToVisualMode {
+            self.changes.start(count.unwrap_or(1));
+            self.changes.record(action);
+        }
+
+        // The count of an insertion repeats the inserted text, once the
+        // change is finished.
+        if action.enters_insert_mode() {
+            return self.execute(action, None, core);
+        }
+        self.execute(action, count, core)
+    }
+
+    /// Executes `action` `count` times and applies the returned `Response`.
+    /// The repetitions stop at the first error.
+    fn execute(
+        &mut self,
+        action: Action,
+        count: Option<usize>,
+        core: &dyn Peer,
+    ) -> Result<Response> {
+        let mut res = Response::Continue;
+
+        for _ in 0..count.unwrap_or(1) {
+            res = match action.execute(&self.view_id, core, &mut self.front_event_writer) {
+                Ok(res) => res,
+                Err(err) => return self.show_error(err, core),
+            };
+
+            match res {
+                Response::RepeatLastChange => return self.repeat_last_change(count, core),
+                Response::ToggleMacroRecording => {
+                    if self.macros.recording_register().is_some() {
+                        self.stop_macro_recording()?;
+                    } else {
+                        self.register_command = Some(RegisterCommand::Record);
+                    }
+                    return Ok(Response::Continue);
                 }
+                Response::PlayMacro => {
+                    self.register_command = Some(RegisterCommand::Play(count.unwrap_or(1)));
+                    return Ok(Response::Continue);
+                }
+                _ => (),
+            }
+            self.apply_response(res, core)?;
+        }
+
+        Ok(res)
+    }
+
+    /// Replays the last change at the current cursor position. A count
+    /// given to `.` replaces the count of the change.
+    fn repeat_last_change(&mut self, count: Option<usize>, core: &dyn Peer) -> Result<Response> {
+        let change = match self.changes.last_change() {
+            Some(change) => change.clone(),
+            None => return Ok(Response::Continue),
+        };
+
+        let actions = change.replay(count.unwrap_or(change.count));
+        self.execute_all(&actions, core)
+    }
+
+    /// Executes `actions` once each, like they were typed.
+    fn execute_all(&mut self, actions: &[Action], core: &dyn Peer) -> Result<Response> {
+        let mut res = Response::Continue;
+        for action in actions {
+            res = match action.execute(&self.view_id, core, &mut self.front_event_writer) {
+                Ok(res) => res,
+                Err(err) => return self.show_error(err, core),
+            };
+            self.apply_response(res, core)?;
+        }
+
+        Ok(res)
+    }
 
-                let res =
-                    action
-                        .unwrap()
-                        .execute(&self.view_id, core, &mut self.front_event_writer);
-
-                match res {
-                    Response::Continue => continue,
-                    Response::Stop => break,
-                    Response::SwitchToInsertMode => self.mode = Mode::Insert,
-                    Response::SwitchToNormalMode => self.mode = Mode::Normal,
-                    Response::SwitchToVisualMode => self.mode = Mode::Visual,
-                    Response::SwitchToActionMode => self.mode = Mode::Action,
+    fn edit_command_line(&mut self, key: KeyStroke, core: &dyn Peer) -> Result<Response> {
+        match key {
+            KeyStroke::Char('\n') => return self.execute_command_line(core),
+            KeyStroke::KeyBackSpace if self.command_line.is_empty() => {
+                self.apply_response(Response::SwitchToNormalMode, core)?;
+                return Ok(Response::Continue);
+            }
+            KeyStroke::KeyBackSpace => {
+                self.command_line.pop();
+            }
+            KeyStroke::Char('\t') => {
+                self.receive_themes();
+                match command::complete(&self.command_line, &self.themes) {
+                    Some(completed) => self.command_line = completed,
+                    None => return Ok(Response::Continue),
                 }
+            }
+            KeyStroke::KeySpace => self.command_line.push(' '),
+            KeyStroke::Char(c) => self.command_line.push(c),
+            _ => return Ok(Response::Continue),
+        }
 
-                core.send_rpc_notification(
-                    "edit",
-                    &json!({ "method": "collapse_selections", "view_id": self.view_id}),
-                );
+        self.show_command_line()?;
+        Ok(Response::Continue)
+    }
 
-                self.front_event_writer.send_rpc_notification(
-                    "update_status_item",
-                    &json!({
-                        "key": "change-mode",
-                        "value": self.mode.to_string(),
-                    }),
-                );
+    /// Keeps the last themes sent by the EventController.
+    fn receive_themes(&mut self) {
+        if let Some(ref themes_receiver) = self.themes_receiver {
+            if let Some(themes) = themes_receiver.try_iter().last() {
+                self.themes = themes;
             }
         }
+    }
+
+    fn execute_command_line(&mut self, core: &dyn Peer) -> Result<Response> {
+        let res = match Command::from_description(&self.command_line) {
+            Ok(Command::Detach) if !self.attached => {
+                self.show_message(String::from("not attached to a session"))?;
+                Response::SwitchToNormalMode
+            }
+            Ok(command) => {
+                match command.execute(&self.view_id, core, &mut self.front_event_writer) {
+                    Ok(Response::ShowMessages) => {
+                        // The command line is hidden while the messages are
+                        // listed.
+                        self.apply_response(Response::SwitchToNormalMode, core)?;
+                        self.show_messages()?;
+                        Response::SwitchToNormalMode
+                    }
+                    Ok(res) => res,
+                    Err(err) => return self.show_error(err, core),
+                }
+            }
+            Err(message) => {
+                self.show_message(message)?;
+                Response::SwitchToNormalMode
+            }
+        };
+
+        self.apply_response(res, core)?;
+        Ok(res)
+    }
+
+    fn show_message(&mut self, message: String) -> Result<()> {
+        self.send_message(message, Severity::Info, false)
+    }
+
+    /// Shows `prompt` until the next message, the user typing a key.
+    fn show_prompt(&mut self, prompt: String) -> Result<()> {
+        self.send_message(prompt, Severity::Info, true)
+    }
+
+    fn send_message(&mut self, message: String, severity: Severity, sticky: bool) -> Result<()> {
+        ClientNotification::ShowMessage {
+            message,
+            severity,
+            sticky,
+        }
+        .send(&mut self.front_event_writer)
+    }
+
+    /// Shows the error of an action on the message line and goes back to
+    /// the normal mode.
+    fn show_error(&mut self, err: Error, core: &dyn Peer) -> Result<Response> {
+        error!("{}", err);
+        self.send_message(err.to_string(), Severity::Error, false)?;
+
+        self.apply_response(Response::SwitchToNormalMode, core)?;
+        Ok(Response::SwitchToNormalMode)
+    }
 
-        self.front_event_writer
-            .send_rpc_notification("command", &json!({"method": "exit"}));
+    fn show_command_line(&mut self) -> Result<()> {
+        let command_line = if self.mode == Mode::Action {
+            Some(self.command_line.clone())
+        } else {
+            None
+        };
+
+        ClientNotification::UpdateCommandLine { command_line }.send(&mut self.front_event_writer)
+    }
+
+    fn handle_register_command(
+        &mut self,
+        command: RegisterCommand,
+        key: KeyStroke,
+        core: &dyn Peer,
+    ) -> Result<Response> {
+        let register = match (command, key) {
+            (RegisterCommand::Play(_), KeyStroke::Char('@')) => match self.last_played_register {
+                Some(register) => register,
+                None => return Ok(Response::Continue),
+            },
+            (_, KeyStroke::Char(c)) if MacroRegisters::is_valid_register(c) => c,
+            // Any other key cancels the command.
+            _ => return Ok(Response::Continue),
+        };
+
+        match command {
+            RegisterCommand::Record => {
+                self.macros.start_recording(register);
+                ClientNotification::AddStatusItem(StatusItem {
+                    key: String::from("macro-recording"),
+                    value: format!("recording @{}", register),
+                    alignment: Alignment::Left,
+                })
+                .send(&mut self.front_event_writer)?;
+                Ok(Response::Continue)
+            }
+            RegisterCommand::Play(count) => self.play_macro(register, count, core),
+        }
+    }
+
+    fn stop_macro_recording(&mut self) -> Result<()> {
+        self.macros.stop_recording();
+        ClientNotification::RemoveStatusItem {
+            key: String::from("macro-recording"),
+        }
+        .send(&mut self.front_event_writer)
+    }
+
+    /// Replays the keys recorded in `register` `count` times, as if they
+    /// were typed by the user.
+    fn play_macro(&mut self, register: char, count: usize, core: &dyn Peer) -> Result<Response> {
+        let keys = match self.macros.get(register) {
+            Some(keys) => keys.repeat(count),
+            None => return Ok(Response::Continue),
+        };
+
+        if self.macro_depth >= MAX_MACRO_DEPTH {
+            warn!("too many nested macros, @{} is ignored", register);
+            return Ok(Response::Continue);
+        }
+
+        self.last_played_register = Some(register);
+        self.macro_depth += 1;
+        // The depth is restored before an error stops the macro.
+        let res = self.replay_keys(keys, core);
+        self.macro_depth -= 1;
+        res
+    }
+
+    /// Handles `keys` like they were typed, until one of them stops the
+    /// frontend.
+    fn replay_keys(&mut self, keys: Vec<KeyStroke>, core: &dyn Peer) -> Result<Response> {
+        let mut keyboard = ReplayKeyboard::new(keys);
+        let mut res = Response::Continue;
+        while let Some(key) = keyboard.get_next_keystroke() {
+            res = self.handle_keystroke(key, core)?;
+            if res == Response::Stop || res == Response::Detach {
+                break;
+            }
+        }
+        Ok(res)
+    }
+
+    fn apply_response(&mut self, res: Response, core: &dyn Peer) -> Result<()> {
+        let mode = match res {
+            Response::SwitchToInsertMode => Mode::Insert,
+            Response::SwitchToNormalMode => Mode::Normal,
+            Response::SwitchToVisualMode => Mode::Visual,
+            Response::SwitchToActionMode => Mode::Action,
+            Response::SwitchToPickerMode => Mode::Picker,
+            _ => self.mode,
+        };
+
+        if mode != self.mode {
+            // The extra cursors are kept in normal mode, only the selected
+            // text is forgotten.
+            if self.mode == Mode::Visual && mode == Mode::Normal {
+                CoreNotification::edit(&self.view_id, EditMethod::CollapseSelections).send(core);
+            }
+
+            let edits_command_line = mode == Mode::Action || self.mode == Mode::Action;
+            self.mode = mode;
+
+            if edits_command_line {
+                self.command_line.clear();
+                self.show_command_line()?;
+            }
+
+            ClientNotification::UpdateStatusItem {
+                key: String::from("change-mode"),
+                value: self.mode.to_string(),
+            }
+            .send(&mut self.front_event_writer)?;
+        }
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use xi_rpc::RemoteError;
+
+    use super::*;
+    use crate::channel::{self, Reader};
+    use crate::tests::MockPeer;
+
+    fn input_controller() -> (InputController, Reader) {
+        let (writer, reader) = channel::channel();
+        let keyboard = ReplayKeyboard::new(Vec::new());
+        let input_controller =
+            InputController::new(Box::new(keyboard), ClientToClientWriter::new(writer));
+        (input_controller, reader)
+    }
+
+    fn type_keys(input_controller: &mut InputController, core: &MockPeer, keys: &[&str]) {
+        let keyboard = ReplayKeyboard::from_descriptions(keys).unwrap();
+        input_controller.set_keyboard(Box::new(keyboard));
+        input_controller.start_keyboard_event_loop(core).unwrap();
+    }
+
+    /// Records a macro selecting and yanking a character in the register
+    /// `a`, the copy failing.
+    fn record_failing_macro(input_controller: &mut InputController) -> MockPeer {
+        let core = MockPeer::new();
+        let copy = CoreRequest::edit(input_controller.view_id(), EditMethod::Copy);
+        core.respond(copy, Err(RemoteError::custom(-32000, "no selection", None)));
+
+        type_keys(input_controller, &core, &["q", "a", "v", "y", "q"]);
+        core
+    }
+
+    #[test]
+    fn replays_a_failing_macro() {
+        let (mut input_controller, _reader) = input_controller();
+        let core = record_failing_macro(&mut input_controller);
+
+        let keys = ["@", "a"].repeat(MAX_MACRO_DEPTH + 1);
+        type_keys(&mut input_controller, &core, &keys);
+
+        assert_eq!(core.requests().len(), MAX_MACRO_DEPTH + 2);
+        assert_eq!(input_controller.macro_depth, 0);
+    }
+
+    #[test]
+    fn restores_the_macro_depth_on_error() {
+        let (mut input_controller, reader) = input_controller();
+        let core = record_failing_macro(&mut input_controller);
+
+        // The error can't be shown once the EventController stopped.
+        drop(reader);
+        let key = KeyStroke::Char('a');
+        input_controller.register_command = Some(RegisterCommand::Play(1));
+        assert!(input_controller.handle_keystroke(key, &core).is_err());
+        assert_eq!(input_controller.macro_depth, 0);
+    }
+}
@@ -1,5 +1,7 @@
+mod replay;
 mod termion;
 
+pub use self::replay::ReplayKeyboard;
 pub use self::termion::TermionKeyboard;
 
 pub trait Keyboard {
@@ -11,6 +13,7 @@ pub enum KeyStroke {
     Char(char),
     KeyF(u8),
     Alt(char),
+    Ctrl(char),
     KeyUp,
     KeyDown,
     KeyLeft,
@@ -25,12 +28,34 @@ pub enum KeyStroke {
 
 impl KeyStroke {
     pub fn from_description(description: &str) -> Option<Self> {
-        if description.len() == 1 {
-            return Some(KeyStroke::Char(description.chars().nth(0).unwrap()));
+        let mut chars = description.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Some(KeyStroke::Char(c));
+        }
+
+        if description.starts_with("<alt-") && description.ends_with('>') {
+            return KeyStroke::from_description(&description[5..description.len() - 1])
+                .and_then(|key| match key {
+                    KeyStroke::Char(c) => Some(KeyStroke::Alt(c)),
+                    _ => None,
+                });
+        }
+
+        if description.starts_with("<ctrl-") && description.ends_with('>') {
+            return KeyStroke::from_description(&description[6..description.len() - 1])
+                .and_then(|key| match key {
+                    KeyStroke::Char(c) => Some(KeyStroke::Ctrl(c)),
+                    _ => None,
+                });
+        }
+
+        if description.starts_with("<f") && description.ends_with('>') {
+            if let Ok(n) = description[2..description.len() - 1].parse() {
+                return Some(KeyStroke::KeyF(n));
+            }
         }
 
         match description {
-            "<f1>" => Some(KeyStroke::KeyF(1)),
             "<key_up>" => Some(KeyStroke::KeyUp),
             "<key_down>" => Some(KeyStroke::KeyDown),
             "<key_left>" => Some(KeyStroke::KeyLeft),
@@ -41,7 +66,34 @@ impl KeyStroke {
             "<del>" => Some(KeyStroke::KeyDelete),
             "<space>" => Some(KeyStroke::KeySpace),
             "<esc>" => Some(KeyStroke::KeyEscape),
+            // The whitespace keys can't be written as is in a sequence of
+            // space-separated descriptions.
+            "<tab>" => Some(KeyStroke::Char('\t')),
+            "<enter>" => Some(KeyStroke::Char('\n')),
             _ => None,
         }
     }
+
+    /// Returns the description of the keystroke, the reverse of
+    /// `from_description`.
+    pub fn to_description(self) -> String {
+        match self {
+            KeyStroke::Char('\t') => String::from("<tab>"),
+            KeyStroke::Char('\n') => String::from("<enter>"),
+            KeyStroke::Char(c) => c.to_string(),
+            KeyStroke::KeyF(n) => format!("<f{}>", n),
+            KeyStroke::Alt(c) => format!("<alt-{}>", c),
+            KeyStroke::Ctrl(c) => format!("<ctrl-{}>", c),
+            KeyStroke::KeyUp => String::from("<key_up>"),
+            KeyStroke::KeyDown => String::from("<key_down>"),
+            KeyStroke::KeyLeft => String::from("<key_left>"),
+            KeyStroke::KeyRight => String::from("<key_right>"),
+            KeyStroke::KeyPreviousPage => String::from("<page_up>"),
+            KeyStroke::KeyNextPage => String::from("<page_down>"),
+            KeyStroke::KeyEscape => String::from("<esc>"),
+            KeyStroke::KeyBackSpace => String::from("<backspace>"),
+            KeyStroke::KeyDelete => String::from("<del>"),
+            KeyStroke::KeySpace => String::from("<space>"),
+        }
+    }
 }
@@ -0,0 +1,33 @@
+use std::collections::VecDeque;
+
+use super::{KeyStroke, Keyboard};
+
+/// A `Keyboard` returning a predefined list of keystrokes, used to replay
+/// the keys recorded in a macro.
+pub struct ReplayKeyboard {
+    keys: VecDeque<KeyStroke>,
+}
+
+impl ReplayKeyboard {
+    pub fn new(keys: Vec<KeyStroke>) -> Self {
+        Self { keys: keys.into() }
+    }
+
+    /// Creates a keyboard typing the keys described in `descriptions`, as
+    /// parsed by `KeyStroke::from_description`. Returns `None` if a
+    /// description is invalid.
+    #[cfg(test)]
+    pub fn from_descriptions(descriptions: &[&str]) -> Option<Self> {
+        let keys: Option<Vec<KeyStroke>> = descriptions
+            .iter()
+            .map(|description| KeyStroke::from_description(description))
+            .collect();
+        keys.map(Self::new)
+    }
+}
+
+impl Keyboard for ReplayKeyboard {
+    fn get_next_keystroke(&mut self) -> Option<KeyStroke> {
+        self.keys.pop_front()
+    }
+}
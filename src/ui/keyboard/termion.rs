@@ -35,7 +35,7 @@ impl<R: Read> Keyboard for TermionKeyboard<R> {
             Key::Insert => None,
             Key::F(n) => Some(KeyStroke::KeyF(n)),
             Key::Alt(c) => Some(KeyStroke::Alt(c)),
-            Key::Ctrl(_) => None,
+            Key::Ctrl(c) => Some(KeyStroke::Ctrl(c)),
             Key::Null => None,
             Key::Esc => Some(KeyStroke::KeyEscape),
             Key::Char(' ') => Some(KeyStroke::KeySpace),
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use super::actions::Action;
+use super::keyboard::KeyStroke;
+use super::Mode;
+
+/// Default bindings for each mode, as `(keys, action)` descriptions.
+///
+/// The keys are a whitespace separated list of `KeyStroke` descriptions
+/// and the action an `Action` description.
+static NORMAL_MODE_BINDINGS: &[(&str, &str)] = &[
+    ("i", "switch_to_insert_mode"),
+    ("v", "switch_to_visual_mode"),
+    (":", "switch_to_action_mode"),
+    ("h", "move_left"),
+    ("j", "move_down"),
+    ("k", "move_up"),
+    ("l", "move_right"),
+    ("<key_left>", "move_left"),
+    ("<key_down>", "move_down"),
+    ("<key_up>", "move_up"),
+    ("<key_right>", "move_right"),
+    ("w", "move_word_right"),
+    ("b", "move_word_left"),
+    ("<page_up>", "page_up"),
+    ("<page_down>", "page_down"),
+    ("g g", "goto_start"),
+    ("G", "goto_end"),
+    ("0", "line_start"),
+    ("$", "line_end"),
+    ("^", "first_non_blank"),
+    ("%", "matching_bracket"),
+    ("<ctrl-o>", "jump_back"),
+    // <ctrl-i> and <tab> are the same key for a terminal.
+    ("<tab>", "jump_forward"),
+    ("<alt-k>", "add_cursor_above"),
+    ("<alt-j>", "add_cursor_below"),
+    ("<ctrl-n>", "select_next_occurrence"),
+    ("<esc>", "collapse_selections"),
+    ("y", "yank_selection"),
+    ("d", "delete_selection"),
+    ("c", "change_selection"),
+    ("x", "delete_forward"),
+    ("X", "delete_backward"),
+    ("p", "paste"),
+    ("o", "insert_line_below"),
+    ("O", "insert_line_above"),
+    (".", "repeat_last_change"),
+    ("q", "toggle_macro_recording"),
+    ("@", "play_macro"),
+    ("<f2>", "toggle_line_numbers"),
+    ("<f3>", "toggle_soft_wrap"),
+];
+
+static INSERT_MODE_BINDINGS: &[(&str, &str)] = &[
+    ("<esc>", "switch_to_normal_mode"),
+    ("<key_left>", "move_left"),
+    ("<key_down>", "move_down"),
+    ("<key_up>", "move_up"),
+    ("<key_right>", "move_right"),
+    ("<backspace>", "delete_backward"),
+    ("<del>", "delete_forward"),
+];
+
+static VISUAL_MODE_BINDINGS: &[(&str, &str)] = &[
+    ("<esc>", "switch_to_normal_mode"),
+    ("h", "move_left_and_select"),
+    ("j", "move_down_and_select"),
+    ("k", "move_up_and_select"),
+    ("l", "move_right_and_select"),
+    ("w", "move_word_right_and_select"),
+    ("b", "move_word_left_and_select"),
+    ("g g", "goto_start_and_select"),
+    ("G", "goto_end_and_select"),
+    ("0", "line_start_and_select"),
+    ("$", "line_end_and_select"),
+    ("<ctrl-n>", "select_next_occurrence"),
+    ("<alt-s>", "split_selection_into_lines"),
+    ("y", "yank_selection"),
+    ("d", "delete_selection"),
+    ("c", "change_selection"),
+    ("p", "delete_selection_and_paste"),
+];
+
+/// The keys which are not bound in the action mode are typed on the
+/// command line.
+static ACTION_MODE_BINDINGS: &[(&str, &str)] = &[("<esc>", "switch_to_normal_mode")];
+
+static PICKER_MODE_BINDINGS: &[(&str, &str)] = &[
+    ("<esc>", "picker_cancel"),
+    ("<enter>", "picker_select"),
+    ("j", "picker_next"),
+    ("k", "picker_previous"),
+    ("<key_down>", "picker_next"),
+    ("<key_up>", "picker_previous"),
+];
+
+/// The result of a `KeyMap` lookup.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum Binding {
+    /// The keys are bound to an action.
+    Action(Action),
+    /// The keys are the beginning of a longer binding, more keys are needed.
+    Prefix,
+    /// Nothing is bound to the keys.
+    Unbound,
+}
+
+/// Maps sequences of keystrokes to actions, for each mode.
+pub struct KeyMap {
+    bindings: HashMap<Mode, HashMap<Vec<KeyStroke>, Action>>,
+}
+
+impl KeyMap {
+    pub fn new() -> Self {
+        Self {
+            bindings: HashMap::new(),
+        }
+    }
+
+    /// Binds the keys described by `keys` to the action described by
+    /// `action` in the given mode.
+    ///
+    /// Returns false if one of the descriptions is invalid.
+    pub fn bind(&mut self, mode: Mode, keys: &str, action: &str) -> bool {
+        let keys: Option<Vec<KeyStroke>> =
+            keys.split_whitespace().map(KeyStroke::from_description).collect();
+
+        match (keys, Action::from_description(action)) {
+            (Some(ref keys), Some(action)) if !keys.is_empty() => {
+                self.bindings
+                    .entry(mode)
+                    .or_default()
+                    .insert(keys.clone(), action);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn lookup(&self, mode: Mode, keys: &[KeyStroke]) -> Binding {
+        let bindings = match self.bindings.get(&mode) {
+            Some(bindings) => bindings,
+            None => return Binding::Unbound,
+        };
+
+        if let Some(action) = bindings.get(keys) {
+            return Binding::Action(*action);
+        }
+
+        if bindings.keys().any(|seq| seq.starts_with(keys)) {
+            return Binding::Prefix;
+        }
+
+        Binding::Unbound
+    }
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let mut keymap = KeyMap::new();
+
+        let modes = [
+            (Mode::Normal, NORMAL_MODE_BINDINGS),
+            (Mode::Insert, INSERT_MODE_BINDINGS),
+            (Mode::Visual, VISUAL_MODE_BINDINGS),
+            (Mode::Action, ACTION_MODE_BINDINGS),
+            (Mode::Picker, PICKER_MODE_BINDINGS),
+        ];
+
+        for (mode, bindings) in modes.iter() {
+            for (keys, action) in bindings.iter() {
+                if !keymap.bind(*mode, keys, action) {
+                    error!("invalid default binding {} -> {}", keys, action);
+                }
+            }
+        }
+
+        keymap
+    }
+}
@@ -0,0 +1,123 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::keyboard::KeyStroke;
+
+/// Named registers holding the keystrokes recorded with `q{register}`.
+pub struct MacroRegisters {
+    registers: HashMap<char, Vec<KeyStroke>>,
+    recording: Option<(char, Vec<KeyStroke>)>,
+    /// File where the registers are persisted, if any.
+    file_path: Option<PathBuf>,
+}
+
+impl MacroRegisters {
+    pub fn new() -> Self {
+        Self {
+            registers: HashMap::new(),
+            recording: None,
+            file_path: None,
+        }
+    }
+
+    /// Loads the registers saved in `file_path` and persists them in it
+    /// each time a recording ends.
+    ///
+    /// A missing file is not an error, it will be created by the first save.
+    pub fn persist_to(&mut self, file_path: &Path) -> io::Result<()> {
+        self.file_path = Some(file_path.to_path_buf());
+
+        let file = match File::open(file_path) {
+            Ok(file) => file,
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+            Err(err) => return Err(err),
+        };
+
+        let saved: HashMap<String, Vec<String>> = serde_json::from_reader(file)?;
+        for (register, descriptions) in saved {
+            let register = match register.chars().next() {
+                Some(register) => register,
+                None => continue,
+            };
+
+            let keys: Option<Vec<KeyStroke>> = descriptions
+                .iter()
+                .map(|desc| KeyStroke::from_description(desc))
+                .collect();
+
+            match keys {
+                Some(keys) => {
+                    self.registers.insert(register, keys);
+                }
+                None => warn!("invalid keystroke in the saved macro @{}", register),
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_valid_register(register: char) -> bool {
+        register.is_ascii_alphanumeric()
+    }
+
+    pub fn recording_register(&self) -> Option<char> {
+        self.recording.as_ref().map(|(register, _)| *register)
+    }
+
+    pub fn start_recording(&mut self, register: char) {
+        self.recording = Some((register, Vec::new()));
+    }
+
+    /// Appends a keystroke to the macro being recorded, if any.
+    pub fn record(&mut self, key: KeyStroke) {
+        if let Some((_, ref mut keys)) = self.recording {
+            keys.push(key);
+        }
+    }
+
+    /// Stores the recorded macro in its register.
+    ///
+    /// The last recorded keystroke is the `q` which ended the recording and
+    /// is not part of the macro.
+    pub fn stop_recording(&mut self) {
+        let (register, mut keys) = match self.recording.take() {
+            Some(recording) => recording,
+            None => return,
+        };
+
+        keys.pop();
+        self.registers.insert(register, keys);
+
+        if let Err(err) = self.save() {
+            error!("failed to save the macros: {}", err);
+        }
+    }
+
+    pub fn get(&self, register: char) -> Option<&[KeyStroke]> {
+        self.registers.get(&register).map(|keys| keys.as_slice())
+    }
+
+    fn save(&self) -> io::Result<()> {
+        let file_path = match self.file_path {
+            Some(ref file_path) => file_path,
+            None => return Ok(()),
+        };
+
+        let saved: HashMap<String, Vec<String>> = self
+            .registers
+            .iter()
+            .map(|(register, keys)| {
+                let descriptions = keys.iter().map(|key| key.to_description()).collect();
+                (register.to_string(), descriptions)
+            })
+            .collect();
+
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        serde_json::to_writer(File::create(file_path)?, &saved)?;
+        Ok(())
+    }
+}
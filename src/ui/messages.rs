@@ -0,0 +1,185 @@
+use std::collections::VecDeque;
+use std::fmt;
+use std::time::Duration;
+
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::status_bar::truncate;
+use crate::ui::terminal::{Color, Style, Terminal};
+
+/// The number of messages kept for `:messages`.
+const HISTORY_LEN: usize = 200;
+
+/// How long a message stays on the status bar.
+pub const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    #[default]
+    Info,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    /// Returns the style of the messages on the status bar, the info ones
+    /// using the style of the status bar.
+    pub fn style(self) -> Style {
+        let (fg, bg) = match self {
+            Severity::Info => return Style::reversed(),
+            Severity::Warning => (Color { r: 0, g: 0, b: 0 }, Color { r: 229, g: 192, b: 123 }),
+            Severity::Error => (Color { r: 255, g: 255, b: 255 }, Color { r: 190, g: 80, b: 70 }),
+        };
+        Style {
+            fg: Some(fg),
+            bg: Some(bg),
+            bold: true,
+            ..Style::default()
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Info => write!(f, "info"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Error => write!(f, "error"),
+        }
+    }
+}
+
+/// A message shown on the status bar, kept in the history.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Message {
+    /// The local time the message was shown, like `14:03:27`.
+    pub time: String,
+    pub severity: Severity,
+    pub text: String,
+}
+
+impl Message {
+    pub fn new(severity: Severity, text: String) -> Self {
+        Message {
+            time: chrono::Local::now().format("%H:%M:%S").to_string(),
+            severity,
+            text,
+        }
+    }
+}
+
+/// The messages shown so far, the oldest first, listed by `:messages`.
+#[derive(Debug, Default)]
+pub struct History {
+    messages: VecDeque<Message>,
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `message`, forgetting the oldest message once the history is
+    /// full.
+    pub fn record(&mut self, message: Message) {
+        if self.messages.len() == HISTORY_LEN {
+            self.messages.pop_front();
+        }
+        self.messages.push_back(message);
+    }
+
+    /// Returns the lines listing the messages. The lines of a message are
+    /// indented below its first line.
+    pub fn lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for message in &self.messages {
+            let mut text = message.text.lines();
+            let first = text.next().unwrap_or_default();
+            lines.push(format!("{} {}: {}", message.time, message.severity, first));
+            lines.extend(text.map(|line| format!("    {}", line)));
+        }
+        lines
+    }
+
+    /// Renders the last messages on the rows above the status bar, over the
+    /// view.
+    pub fn render(&self, terminal: &mut dyn Terminal) {
+        let (width, rows) = terminal.size();
+        let width = width as usize;
+        let mut lines = self.lines();
+        if lines.is_empty() {
+            lines.push(String::from("no message"));
+        }
+
+        let shown = lines.len().min(rows.saturating_sub(1) as usize);
+        if shown == 0 {
+            return;
+        }
+
+        let top = rows as usize - 1 - shown;
+        for (idx, line) in lines[lines.len() - shown..].iter().enumerate() {
+            let text = truncate(line, width);
+            let padding = " ".repeat(width - text.width());
+            terminal.draw(0, (top + idx) as u16, &format!("{}{}", text, padding), Style::default());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::terminal::MemoryTerminal;
+
+    fn message(severity: Severity, text: &str) -> Message {
+        Message {
+            time: String::from("14:03:27"),
+            severity,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn lists_the_messages() {
+        let mut history = History::new();
+        history.record(message(Severity::Info, "written"));
+        history.record(message(Severity::Error, "plugin crashed\nexit code 1"));
+
+        assert_eq!(
+            history.lines(),
+            vec![
+                "14:03:27 info: written",
+                "14:03:27 error: plugin crashed",
+                "    exit code 1",
+            ]
+        );
+    }
+
+    #[test]
+    fn forgets_the_oldest_messages() {
+        let mut history = History::new();
+        for idx in 0..HISTORY_LEN + 2 {
+            history.record(message(Severity::Info, &idx.to_string()));
+        }
+
+        let lines = history.lines();
+        assert_eq!(lines.len(), HISTORY_LEN);
+        assert_eq!(lines[0], "14:03:27 info: 2");
+    }
+
+    #[test]
+    fn renders_the_last_messages() {
+        let mut history = History::new();
+        history.record(message(Severity::Info, "first"));
+        history.record(message(Severity::Info, "second\nthird"));
+
+        let mut terminal = MemoryTerminal::new(24, 3);
+        history.render(&mut terminal);
+        assert_eq!(terminal.rows(), vec!["14:03:27 info: second", "    third", ""]);
+
+        // Nothing fits above the status bar.
+        let mut terminal = MemoryTerminal::new(24, 0);
+        history.render(&mut terminal);
+        assert_eq!(terminal.rows(), Vec::<String>::new());
+    }
+}
@@ -2,6 +2,48 @@
 //use self::controller;
 
 pub mod keyboard;
+pub mod messages;
+pub mod picker;
+pub mod status_bar;
+pub mod style;
+pub mod terminal;
+pub mod view;
+mod actions;
+mod command;
 mod controller;
+mod keymap;
+mod macros;
+mod repeat;
 
-use self::controller::InputController;
\ No newline at end of file
+use std::fmt;
+use std::sync::Mutex;
+
+use self::keyboard::KeyStroke;
+
+pub use self::controller::InputController;
+
+/// The text yanked or cut by the last `YankSelection`/`DeleteSelection`,
+/// shared by every view.
+pub static PASTE_BUFFER: Mutex<Option<String>> = Mutex::new(None);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub enum Mode {
+    Normal,
+    Insert,
+    Visual,
+    Action,
+    /// An item is picked from a list, like the language of the view.
+    Picker,
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Normal => write!(f, "NORMAL"),
+            Mode::Insert => write!(f, "INSERT"),
+            Mode::Visual => write!(f, "VISUAL"),
+            Mode::Action => write!(f, "ACTION"),
+            Mode::Picker => write!(f, "PICKER"),
+        }
+    }
+}
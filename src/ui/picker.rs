@@ -0,0 +1,88 @@
+use unicode_width::UnicodeWidthStr;
+
+use crate::ui::status_bar::truncate;
+use crate::ui::terminal::{Style, Terminal};
+
+/// The maximum number of items displayed at once.
+const MAX_ROWS: usize = 10;
+
+/// What is picked, deciding what happens once an item is selected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PickerKind {
+    Language,
+    /// The themes are previewed as they are highlighted.
+    Theme,
+}
+
+/// A list of items displayed above the status bar, one of them being
+/// highlighted.
+#[derive(Debug, Clone)]
+pub struct Picker {
+    pub kind: PickerKind,
+    /// The view the picked item applies to.
+    pub view_id: String,
+    /// The current item when the picker was opened.
+    pub initial: Option<String>,
+    items: Vec<String>,
+    selected: usize,
+}
+
+impl Picker {
+    /// Creates a picker highlighting `current` if it is one of the items.
+    pub fn new(kind: PickerKind, view_id: &str, items: Vec<String>, current: Option<&str>) -> Self {
+        let selected = current
+            .and_then(|current| items.iter().position(|item| item == current))
+            .unwrap_or(0);
+
+        Picker {
+            kind,
+            view_id: view_id.to_string(),
+            initial: current.map(String::from),
+            items,
+            selected,
+        }
+    }
+
+    /// Moves the highlight by `delta` items, wrapping around the list.
+    pub fn move_by(&mut self, delta: i64) {
+        if self.items.is_empty() {
+            return;
+        }
+
+        let len = self.items.len() as i64;
+        self.selected = (self.selected as i64 + delta).rem_euclid(len) as usize;
+    }
+
+    /// Returns the highlighted item.
+    pub fn selected(&self) -> Option<&str> {
+        self.items.get(self.selected).map(String::as_str)
+    }
+
+    /// Renders the items on the rows above the status bar.
+    pub fn render(&self, terminal: &mut dyn Terminal) {
+        let (width, rows) = terminal.size();
+        let width = width as usize;
+        let shown = self.items.len().min(MAX_ROWS).min(rows.saturating_sub(1) as usize);
+        if shown == 0 {
+            return;
+        }
+
+        // Scrolls the list to keep the highlighted item visible.
+        let first = (self.selected + 1).saturating_sub(shown);
+        let top = rows as usize - 1 - shown;
+
+        for (idx, item) in self.items.iter().enumerate().skip(first).take(shown) {
+            let style = if idx == self.selected {
+                Style::reversed()
+            } else {
+                Style::default()
+            };
+            let text = truncate(&format!(" {}", item), width);
+            let padding = " ".repeat(width - text.width());
+            let row = (top + idx - first) as u16;
+
+            terminal.draw(0, row, &format!("{}{}", text, padding), style);
+        }
+    }
+}
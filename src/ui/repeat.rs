@@ -0,0 +1,197 @@
+use super::actions::Action;
+
+/// A complete change, as typed by the user from the normal mode.
+///
+/// The first action is the command which started the change, the text
+/// inserted by the change is kept as `Action::InsertKeyStroke` actions. The
+/// count repeats the command, or the text inserted once the command
+/// switched to the insert mode: `3ihi<esc>` inserts `hihihi`, and
+/// `3ohi<esc>` opens three lines with `hi`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct Change {
+    pub count: usize,
+    pub actions: Vec<Action>,
+}
+
+impl Change {
+    /// Returns the actions replaying the change, with `count` instead of
+    /// its own count.
+    pub fn replay(&self, count: usize) -> Vec<Action> {
+        let (first, rest) = match self.actions.split_first() {
+            Some((first, rest)) => (*first, rest),
+            None => return Vec::new(),
+        };
+
+        if first.enters_insert_mode() {
+            let mut actions = self.actions.clone();
+            actions.extend(self.repetitions(count));
+            return actions;
+        }
+
+        let mut actions = vec![first; count];
+        actions.extend_from_slice(rest);
+        actions
+    }
+
+    /// Returns the actions inserting the text of the change `count - 1`
+    /// more times, once it was inserted by the change. They end back in
+    /// the normal mode.
+    fn repetitions(&self, count: usize) -> Vec<Action> {
+        let (first, rest) = match self.actions.split_first() {
+            Some((first, rest)) if first.enters_insert_mode() && count > 1 => (*first, rest),
+            _ => return Vec::new(),
+        };
+        let text = rest.strip_suffix(&[Action::SwitchToNormalMode]).unwrap_or(rest);
+
+        // The opened lines are opened again, the other commands only
+        // switched to the insert mode.
+        let mut repeated = Vec::new();
+        if first != Action::SwitchToInsertMode {
+            repeated.push(first);
+        }
+        repeated.extend_from_slice(text);
+
+        let mut actions = repeated.repeat(count - 1);
+        actions.push(Action::SwitchToNormalMode);
+        actions
+    }
+}
+
+/// Records the actions executed by the `InputController` in order to
+/// replay the last change with the `RepeatLastChange` action.
+#[derive(Debug, Default)]
+pub struct ChangeRecorder {
+    current: Option<Change>,
+    last: Option<Change>,
+}
+
+impl ChangeRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts recording a new change, dropping the unfinished one if any.
+    pub fn start(&mut self, count: usize) {
+        self.current = Some(Change {
+            count,
+            actions: Vec::new(),
+        });
+    }
+
+    /// Appends an action to the change being recorded, if any.
+    pub fn record(&mut self, action: Action) {
+        if let Some(ref mut change) = self.current {
+            change.actions.push(action);
+        }
+    }
+
+    /// Ends the current recording, and returns the actions inserting its
+    /// text again when it was typed with a count.
+    ///
+    /// The recorded change replaces the last one only if it modified the
+    /// buffer: a selection which was only yanked is not a change.
+    pub fn finish(&mut self) -> Vec<Action> {
+        let change = match self.current.take() {
+            Some(change) => change,
+            None => return Vec::new(),
+        };
+        if !change.actions.iter().any(|action| action.is_change()) {
+            return Vec::new();
+        }
+
+        let repetitions = change.repetitions(change.count);
+        self.last = Some(change);
+        repetitions
+    }
+
+    pub fn last_change(&self) -> Option<&Change> {
+        self.last.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::keyboard::KeyStroke;
+
+    fn record(recorder: &mut ChangeRecorder, count: usize, actions: &[Action]) -> Vec<Action> {
+        recorder.start(count);
+        for &action in actions {
+            recorder.record(action);
+        }
+        recorder.finish()
+    }
+
+    fn insert(text: &str) -> Vec<Action> {
+        text.chars().map(|c| Action::InsertKeyStroke(KeyStroke::Char(c))).collect()
+    }
+
+    #[test]
+    fn repeats_the_command() {
+        let mut recorder = ChangeRecorder::new();
+        assert_eq!(record(&mut recorder, 3, &[Action::DeleteForward]), vec![]);
+
+        let change = recorder.last_change().unwrap();
+        assert_eq!(change.replay(change.count), vec![Action::DeleteForward; 3]);
+        assert_eq!(change.replay(2), vec![Action::DeleteForward; 2]);
+    }
+
+    #[test]
+    fn repeats_the_inserted_text() {
+        let mut recorder = ChangeRecorder::new();
+        let mut typed = vec![Action::SwitchToInsertMode];
+        typed.extend(insert("hi"));
+        typed.push(Action::SwitchToNormalMode);
+
+        let mut repetitions = insert("hihi");
+        repetitions.push(Action::SwitchToNormalMode);
+        assert_eq!(record(&mut recorder, 3, &typed), repetitions);
+
+        let mut replayed = typed.clone();
+        replayed.extend(repetitions);
+        assert_eq!(recorder.last_change().unwrap().replay(3), replayed);
+        assert_eq!(recorder.last_change().unwrap().replay(1), typed);
+    }
+
+    #[test]
+    fn repeats_the_opened_lines() {
+        let mut recorder = ChangeRecorder::new();
+        let mut typed = vec![Action::InsertLineBelow];
+        typed.extend(insert("a"));
+        typed.push(Action::SwitchToNormalMode);
+
+        let repetitions = record(&mut recorder, 2, &typed);
+        let mut expected = vec![Action::InsertLineBelow];
+        expected.extend(insert("a"));
+        expected.push(Action::SwitchToNormalMode);
+        assert_eq!(repetitions, expected);
+    }
+
+    #[test]
+    fn keeps_the_last_change() {
+        let mut recorder = ChangeRecorder::new();
+        record(&mut recorder, 1, &[Action::DeleteForward]);
+
+        // A yanked selection doesn't modify the buffer.
+        record(
+            &mut recorder,
+            1,
+            &[Action::SwitchToVisualMode, Action::MoveRightAndSelect, Action::YankSelection],
+        );
+        assert_eq!(recorder.last_change().unwrap().actions, vec![Action::DeleteForward]);
+
+        // An unfinished change is dropped by the next one.
+        recorder.start(1);
+        recorder.record(Action::Paste);
+        record(&mut recorder, 1, &[Action::DeleteBackward]);
+        assert_eq!(recorder.last_change().unwrap().actions, vec![Action::DeleteBackward]);
+    }
+
+    #[test]
+    fn records_only_while_started() {
+        let mut recorder = ChangeRecorder::new();
+        recorder.record(Action::DeleteForward);
+        assert_eq!(recorder.finish(), vec![]);
+        assert_eq!(recorder.last_change(), None);
+    }
+}
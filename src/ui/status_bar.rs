@@ -0,0 +1,142 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::ui::messages::Severity;
+use crate::ui::terminal::{Style, Terminal};
+
+const SEPARATOR: &str = " | ";
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Alignment {
+    #[default]
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+struct StatusItem {
+    key: String,
+    value: String,
+    alignment: Alignment,
+}
+
+/// The last row of the screen, displaying the status items, the last
+/// message and the command line while it is edited.
+#[derive(Debug, Default)]
+pub struct StatusBar {
+    items: Vec<StatusItem>,
+    message: Option<(Severity, String)>,
+    command_line: Option<String>,
+}
+
+impl StatusBar {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_item(&mut self, key: &str, value: &str, alignment: Alignment) {
+        self.remove_item(key);
+        self.items.push(StatusItem {
+            key: key.to_string(),
+            value: value.to_string(),
+            alignment,
+        });
+    }
+
+    pub fn update_item(&mut self, key: &str, value: &str) {
+        match self.items.iter_mut().find(|item| item.key == key) {
+            Some(item) => item.value = value.to_string(),
+            None => warn!("update of the unknown status item {}", key),
+        }
+    }
+
+    pub fn remove_item(&mut self, key: &str) {
+        self.items.retain(|item| item.key != key);
+    }
+
+    /// Shows `message` in the color of its severity. Only its first line
+    /// fits in the status bar, the next ones follow it.
+    pub fn set_message(&mut self, severity: Severity, message: &str) {
+        let message = message.lines().collect::<Vec<_>>().join(" ");
+        self.message = Some((severity, message));
+    }
+
+    pub fn clear_message(&mut self) {
+        self.message = None;
+    }
+
+    /// Sets the text of the command line, `None` once the edition is over.
+    pub fn set_command_line(&mut self, command_line: Option<String>) {
+        self.command_line = command_line;
+    }
+
+    /// Renders the status bar on the last row of the terminal.
+    pub fn render(&self, terminal: &mut dyn Terminal) {
+        let (width, rows) = terminal.size();
+        if rows == 0 {
+            return;
+        }
+        let row = rows - 1;
+        let width = width as usize;
+
+        if let Some(ref command_line) = self.command_line {
+            let text = truncate(&format!(":{}", command_line), width);
+            let text_width = text.width();
+
+            terminal.draw(0, row, &pad(text, width), Style::default());
+            terminal.set_cursor(Some((text_width.min(width.saturating_sub(1)) as u16, row)));
+            return;
+        }
+
+        let values = |alignment: Alignment| -> Vec<&str> {
+            self.items
+                .iter()
+                .filter(|item| item.alignment == alignment)
+                .map(|item| item.value.as_str())
+                .collect()
+        };
+
+        let mut left = values(Alignment::Left);
+        // The column of the message, after the items.
+        let message_x = format!(" {}", left.join(SEPARATOR)).width()
+            + if left.is_empty() { 0 } else { SEPARATOR.width() };
+        if let Some((_, ref message)) = self.message {
+            left.push(message);
+        }
+        let left = left.join(SEPARATOR);
+        let right = values(Alignment::Right).join(SEPARATOR);
+
+        // The items on the left are more important, the right ones are cut
+        // first when the screen is too small.
+        let left = truncate(&format!(" {}", left), width);
+        let right = truncate(&format!("{} ", right), width - left.width());
+        let padding = " ".repeat(width - left.width() - right.width());
+
+        terminal.draw(0, row, &format!("{}{}{}", left, padding, right), Style::reversed());
+
+        // The warnings and errors are drawn again in their color.
+        if let Some((severity, ref message)) = self.message {
+            if severity != Severity::Info && message_x < left.width() {
+                let message = truncate(message, left.width() - message_x);
+                terminal.draw(message_x as u16, row, &message, severity.style());
+            }
+        }
+    }
+}
+
+/// Returns the longest beginning of `text` fitting in `width` columns.
+pub fn truncate(text: &str, width: usize) -> String {
+    let mut columns = 0;
+    text.chars()
+        .take_while(|c| {
+            columns += c.width().unwrap_or(0);
+            columns <= width
+        })
+        .collect()
+}
+
+/// Fills `text` with spaces up to `width` columns.
+fn pad(text: String, width: usize) -> String {
+    let padding = " ".repeat(width.saturating_sub(text.width()));
+    text + &padding
+}
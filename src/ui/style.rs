@@ -0,0 +1,61 @@
+use std::collections::HashMap;
+
+use crate::protocol::{Rgba, StyleDef, Theme};
+use crate::ui::terminal::{Color, Style};
+
+/// xi-core considers a font weight above this one as bold.
+const BOLD_WEIGHT: u64 = 700;
+
+/// The styles defined by xi-core with `def_style`, referenced by their id
+/// in the lines, and the colors of the current theme.
+#[derive(Debug, Default)]
+pub struct Styles {
+    styles: HashMap<u64, Style>,
+    /// The style of the text without any other style.
+    base: Style,
+}
+
+impl Styles {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the style defined by a `def_style` notification.
+    pub fn define(&mut self, style: &StyleDef) {
+        let color = |argb: Option<u32>| argb.map(Color::from_argb);
+        let definition = Style {
+            fg: color(style.fg_color),
+            bg: color(style.bg_color),
+            bold: matches!(style.weight, Some(weight) if weight >= BOLD_WEIGHT),
+            italic: style.italic,
+            underline: style.underline,
+            reverse: false,
+        };
+
+        self.styles.insert(style.id, definition);
+    }
+
+    /// Uses the foreground and background of a theme as the base style.
+    pub fn set_theme(&mut self, theme: &Theme) {
+        let color = |rgba: Option<Rgba>| rgba.map(|Rgba { r, g, b, .. }| Color { r, g, b });
+
+        self.base = Style {
+            fg: color(theme.foreground),
+            bg: color(theme.background),
+            ..Style::default()
+        };
+    }
+
+    pub fn base(&self) -> Style {
+        self.base
+    }
+
+    /// Returns the style `id`, its missing colors being the base ones.
+    pub fn get(&self, id: u64) -> Option<Style> {
+        self.styles.get(&id).map(|style| Style {
+            fg: style.fg.or(self.base.fg),
+            bg: style.bg.or(self.base.bg),
+            ..*style
+        })
+    }
+}
@@ -0,0 +1,93 @@
+use std::io;
+use std::sync::{Arc, Mutex};
+
+use unicode_width::UnicodeWidthChar;
+
+use super::{Style, Terminal};
+
+/// A cell of the screen, the second column of a wide character being a
+/// `None` cell.
+type Cell = (Option<char>, Style);
+
+struct Screen {
+    size: (u16, u16),
+    cells: Vec<Vec<Cell>>,
+    cursor: Option<(u16, u16)>,
+}
+
+/// A `Terminal` keeping the screen in memory, to render without a real
+/// terminal.
+///
+/// The clones share the same screen, a clone can be kept to read the screen
+/// once the terminal is given to the `EventController`.
+#[derive(Clone)]
+pub struct MemoryTerminal {
+    screen: Arc<Mutex<Screen>>,
+}
+
+impl MemoryTerminal {
+    pub fn new(columns: u16, rows: u16) -> Self {
+        let blank = (Some(' '), Style::default());
+        let screen = Screen {
+            size: (columns, rows),
+            cells: vec![vec![blank; columns as usize]; rows as usize],
+            cursor: None,
+        };
+
+        Self {
+            screen: Arc::new(Mutex::new(screen)),
+        }
+    }
+
+    /// Returns the text of each row, without the trailing spaces.
+    pub fn rows(&self) -> Vec<String> {
+        let screen = self.screen.lock().unwrap();
+        screen
+            .cells
+            .iter()
+            .map(|row| {
+                let text: String = row.iter().filter_map(|&(c, _)| c).collect();
+                text.trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+impl Terminal for MemoryTerminal {
+    fn size(&self) -> (u16, u16) {
+        self.screen.lock().unwrap().size
+    }
+
+    fn draw(&mut self, x: u16, y: u16, text: &str, style: Style) {
+        let mut screen = self.screen.lock().unwrap();
+        let row = match screen.cells.get_mut(y as usize) {
+            Some(row) => row,
+            None => return,
+        };
+
+        let mut column = x as usize;
+        for c in text.chars() {
+            let width = c.width().unwrap_or(0);
+            if width == 0 {
+                continue;
+            }
+            if column + width > row.len() {
+                break;
+            }
+
+            row[column] = (Some(c), style);
+            if width == 2 {
+                row[column + 1] = (None, style);
+            }
+            column += width;
+        }
+    }
+
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) {
+        self.screen.lock().unwrap().cursor = position;
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
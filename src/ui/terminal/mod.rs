@@ -0,0 +1,63 @@
+mod memory;
+mod termion;
+
+use std::io;
+
+pub use self::memory::MemoryTerminal;
+pub use self::termion::{restore_terminal, TermionTerminal};
+
+/// The screen the views are rendered on.
+///
+/// Positions are given as `(column, row)`, starting at `(0, 0)` at the top
+/// left of the screen.
+pub trait Terminal {
+    /// Returns the size of the screen as `(columns, rows)`.
+    fn size(&self) -> (u16, u16);
+
+    /// Draws `text` at the given position. The text is expected to fit in
+    /// the row.
+    fn draw(&mut self, x: u16, y: u16, text: &str, style: Style);
+
+    /// Moves the cursor to the given position, or hides it.
+    fn set_cursor(&mut self, position: Option<(u16, u16)>);
+
+    fn flush(&mut self) -> io::Result<()>;
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Color {
+    pub r: u8,
+    pub g: u8,
+    pub b: u8,
+}
+
+impl Color {
+    /// Creates a color from the ARGB value used by xi-core, the alpha
+    /// channel is ignored.
+    pub fn from_argb(argb: u32) -> Self {
+        Color {
+            r: (argb >> 16) as u8,
+            g: (argb >> 8) as u8,
+            b: argb as u8,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Style {
+    pub fg: Option<Color>,
+    pub bg: Option<Color>,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub reverse: bool,
+}
+
+impl Style {
+    pub fn reversed() -> Self {
+        Style {
+            reverse: true,
+            ..Style::default()
+        }
+    }
+}
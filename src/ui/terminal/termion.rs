@@ -0,0 +1,128 @@
+use std::io::{self, Stdout, Write};
+use std::mem;
+use std::sync::Mutex;
+
+use super::{Style, Terminal};
+
+use termion::raw::{IntoRawMode, RawTerminal};
+use termion::screen::{self, AlternateScreen};
+use termion::color::{self, Rgb};
+use termion::{cursor, style};
+
+/// The attributes of the terminal before the raw mode, restored by
+/// `restore_terminal`.
+static COOKED_MODE: Mutex<Option<libc::termios>> = Mutex::new(None);
+
+/// A `Terminal` drawing on the standard output.
+///
+/// The terminal is put in raw mode and switched to the alternate screen
+/// until the value is dropped.
+pub struct TermionTerminal {
+    out: AlternateScreen<RawTerminal<Stdout>>,
+    size: (u16, u16),
+}
+
+impl TermionTerminal {
+    pub fn new() -> io::Result<Self> {
+        save_cooked_mode()?;
+        let out = AlternateScreen::from(io::stdout().into_raw_mode()?);
+        let size = termion::terminal_size()?;
+
+        Ok(Self { out, size })
+    }
+}
+
+fn save_cooked_mode() -> io::Result<()> {
+    // Safe: `termios` is a plain C struct, filled by `tcgetattr`.
+    let mut termios: libc::termios = unsafe { mem::zeroed() };
+    if unsafe { libc::tcgetattr(libc::STDOUT_FILENO, &mut termios) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    *COOKED_MODE.lock().unwrap() = Some(termios);
+    Ok(())
+}
+
+/// Leaves the alternate screen and the raw mode, and shows the cursor.
+///
+/// Used after a panic: the `TermionTerminal` restoring the terminal when it
+/// is dropped may be owned by another thread, which never drops it. Does
+/// nothing if no `TermionTerminal` was created.
+pub fn restore_terminal() {
+    // The lock is held by this thread if it panicked while creating the
+    // terminal.
+    let cooked_mode = match COOKED_MODE.try_lock() {
+        Ok(cooked_mode) => *cooked_mode,
+        Err(_) => return,
+    };
+    let termios = match cooked_mode {
+        Some(termios) => termios,
+        None => return,
+    };
+
+    let mut out = io::stdout();
+    let _ = write!(out, "{}{}{}", style::Reset, cursor::Show, screen::ToMainScreen);
+    let _ = out.flush();
+    unsafe {
+        libc::tcsetattr(libc::STDOUT_FILENO, libc::TCSANOW, &termios);
+    }
+}
+
+impl TermionTerminal {
+    fn draw_styled(&mut self, x: u16, y: u16, text: &str, style: Style) -> io::Result<()> {
+        write!(self.out, "{}", cursor::Goto(x + 1, y + 1))?;
+
+        if let Some(fg) = style.fg {
+            write!(self.out, "{}", color::Fg(Rgb(fg.r, fg.g, fg.b)))?;
+        }
+        if let Some(bg) = style.bg {
+            write!(self.out, "{}", color::Bg(Rgb(bg.r, bg.g, bg.b)))?;
+        }
+        if style.bold {
+            write!(self.out, "{}", style::Bold)?;
+        }
+        if style.italic {
+            write!(self.out, "{}", style::Italic)?;
+        }
+        if style.underline {
+            write!(self.out, "{}", style::Underline)?;
+        }
+        if style.reverse {
+            write!(self.out, "{}", style::Invert)?;
+        }
+
+        write!(self.out, "{}{}", text, style::Reset)
+    }
+}
+
+impl Terminal for TermionTerminal {
+    fn size(&self) -> (u16, u16) {
+        self.size
+    }
+
+    fn draw(&mut self, x: u16, y: u16, text: &str, style: Style) {
+        if let Err(err) = self.draw_styled(x, y, text, style) {
+            error!("failed to draw on the terminal: {}", err);
+        }
+    }
+
+    fn set_cursor(&mut self, position: Option<(u16, u16)>) {
+        let res = match position {
+            Some((x, y)) => write!(self.out, "{}{}", cursor::Goto(x + 1, y + 1), cursor::Show),
+            None => write!(self.out, "{}", cursor::Hide),
+        };
+
+        if let Err(err) = res {
+            error!("failed to move the cursor: {}", err);
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        // The terminal can be resized at any time, pick up the new size for
+        // the next rendering.
+        if let Ok(size) = termion::terminal_size() {
+            self.size = size;
+        }
+        self.out.flush()
+    }
+}
@@ -0,0 +1,56 @@
+use super::Cursor;
+
+/// Maximum number of positions kept in a `JumpList`.
+const MAX_JUMPS: usize = 100;
+
+/// The positions the cursor jumped from, used to go back and forward
+/// through the recent large movements of a view.
+#[derive(Debug, Default)]
+pub struct JumpList {
+    jumps: Vec<Cursor>,
+    /// Index of the current position while navigating in the list, equal to
+    /// the number of jumps otherwise.
+    current: usize,
+}
+
+impl JumpList {
+    /// Records the position the cursor is about to jump from.
+    ///
+    /// A line is only kept once in the list, the previous jump on the same
+    /// line is forgotten.
+    pub fn push(&mut self, from: Cursor) {
+        self.jumps.retain(|jump| jump.line != from.line);
+        self.jumps.push(from);
+        if self.jumps.len() > MAX_JUMPS {
+            self.jumps.remove(0);
+        }
+        self.current = self.jumps.len();
+    }
+
+    /// Returns the position to go back to, `from` being the current
+    /// position of the cursor.
+    pub fn back(&mut self, from: Cursor) -> Option<Cursor> {
+        if self.current == self.jumps.len() {
+            // Remember where we started navigating so we can come back to it.
+            self.push(from);
+            self.current = self.jumps.len() - 1;
+        }
+
+        if self.current == 0 {
+            return None;
+        }
+
+        self.current -= 1;
+        Some(self.jumps[self.current])
+    }
+
+    /// Returns the position to go forward to, after going back.
+    pub fn forward(&mut self) -> Option<Cursor> {
+        if self.current + 1 >= self.jumps.len() {
+            return None;
+        }
+
+        self.current += 1;
+        Some(self.jumps[self.current])
+    }
+}
@@ -0,0 +1,85 @@
+use crate::protocol::{Line, Update, UpdateOpKind};
+
+/// Keeps the lines sent by xi-core for a view.
+///
+/// The lines which are not known yet or have been invalidated are `None`.
+#[derive(Debug, Default)]
+pub struct LineCache {
+    lines: Vec<Option<Line>>,
+}
+
+impl LineCache {
+    pub fn height(&self) -> usize {
+        self.lines.len()
+    }
+
+    pub fn get(&self, idx: usize) -> Option<&Line> {
+        self.lines.get(idx).and_then(|line| line.as_ref())
+    }
+
+    /// Returns the number of the logical line displayed on the row `idx`,
+    /// the rows continuing a wrapped line having no number of their own.
+    /// `None` if the line is not known.
+    pub fn line_number(&self, idx: usize) -> Option<usize> {
+        self.lines
+            .get(..=idx)?
+            .iter()
+            .rev()
+            .map_while(Option::as_ref)
+            .find_map(|line| line.ln)
+    }
+
+    /// Returns the number of the last known logical line.
+    pub fn last_line_number(&self) -> Option<usize> {
+        self.lines.iter().rev().find_map(|line| line.as_ref()?.ln)
+    }
+
+    /// Returns the text of the view, `None` if some lines are not known.
+    pub fn text(&self) -> Option<String> {
+        // The wrapped lines are split in several lines, each logical line
+        // ending with its newline.
+        self.lines
+            .iter()
+            .map(|line| line.as_ref().map(|line| line.text.as_str()))
+            .collect()
+    }
+
+    /// Applies the `update` param of an `update` notification.
+    pub fn update(&mut self, update: &Update) {
+        let mut old_lines = std::mem::take(&mut self.lines).into_iter();
+
+        for op in &update.ops {
+            match op.op {
+                UpdateOpKind::Copy => {
+                    let mut ln = op.ln;
+                    for mut line in old_lines.by_ref().take(op.n) {
+                        if let (Some(line), Some(new_ln)) = (line.as_mut(), ln) {
+                            if line.ln.is_some() {
+                                line.ln = Some(new_ln);
+                                ln = Some(new_ln + 1);
+                            }
+                        }
+                        self.lines.push(line);
+                    }
+                }
+                UpdateOpKind::Skip => {
+                    old_lines.by_ref().take(op.n).for_each(drop);
+                }
+                UpdateOpKind::Invalidate => self.lines.extend((0..op.n).map(|_| None)),
+                UpdateOpKind::Ins => self.lines.extend(op.lines.iter().cloned().map(Some)),
+                UpdateOpKind::Update => {
+                    for (old, new) in old_lines.by_ref().take(op.n).zip(&op.lines) {
+                        // xi-core does not send the text of the updated lines,
+                        // only their cursors and styles.
+                        self.lines.push(old.map(|mut old| {
+                            old.cursor = new.cursor.clone();
+                            old.styles = new.styles.clone();
+                            old.ln = new.ln.or(old.ln);
+                            old
+                        }));
+                    }
+                }
+            }
+        }
+    }
+}
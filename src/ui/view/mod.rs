@@ -0,0 +1,259 @@
+mod jump_list;
+mod line_cache;
+mod render;
+mod settings;
+
+use std::collections::BTreeMap;
+
+use crate::protocol::{Update, UpdateOpKind};
+
+pub use self::jump_list::JumpList;
+pub use self::line_cache::LineCache;
+pub use self::render::text_width;
+pub use self::settings::Settings;
+pub use crate::protocol::Line;
+
+const BRACKETS: &[(char, char)] = &[('(', ')'), ('[', ']'), ('{', '}')];
+
+/// A position in a view, the column being a byte offset in the line.
+#[derive(Debug, Copy, Clone, Default, Eq, PartialEq)]
+pub struct Cursor {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// A selected region, from `start` included to `end` excluded.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Selection {
+    pub start: Cursor,
+    pub end: Cursor,
+}
+
+/// The state kept by the frontend for each view opened in xi-core.
+#[derive(Debug, Default)]
+pub struct View {
+    pub path: Option<String>,
+    pub lines: LineCache,
+    /// The primary cursor, the one xi-core scrolls to.
+    pub cursor: Cursor,
+    pub selections: Vec<Selection>,
+    pub jumps: JumpList,
+    /// The plugins available for the view, and whether they are running.
+    pub plugins: BTreeMap<String, bool>,
+    /// The language used by xi-core for the syntax highlighting.
+    pub language: Option<String>,
+    pub settings: Settings,
+
+    /// False while the buffer has unsaved changes.
+    pub pristine: bool,
+    /// The text changed since the swap file was written.
+    pub swap_outdated: bool,
+    /// A swap file was written for the view.
+    pub has_swap: bool,
+
+    /// The wrap width last sent to xi-core, 0 when the lines are not
+    /// wrapped.
+    pub wrap_width: Option<usize>,
+
+    /// The first line displayed on the screen.
+    first_line: usize,
+    /// The first column displayed when the lines are not wrapped.
+    first_col: usize,
+    /// The number of columns available for the text on the screen.
+    width: usize,
+    /// The number of lines displayed on the screen.
+    height: usize,
+    /// The lines last requested to xi-core with a `scroll`.
+    scroll_region: Option<(usize, usize)>,
+}
+
+impl View {
+    /// Applies the `update` param of an `update` notification.
+    pub fn update(&mut self, update: &Update) {
+        self.lines.update(update);
+        self.pristine = update.pristine;
+        // Only the updates inserting, updating or skipping lines may change
+        // the text, the ones copying the whole cache don't.
+        let changes_text = update.ops.iter().any(|op| {
+            matches!(op.op, UpdateOpKind::Ins | UpdateOpKind::Update | UpdateOpKind::Skip)
+        });
+        if update.pristine {
+            self.swap_outdated = false;
+        } else if changes_text {
+            self.swap_outdated = true;
+        }
+
+        self.selections = update
+            .annotations
+            .iter()
+            .filter(|annotation| annotation.kind == "selection")
+            .flat_map(|annotation| annotation.ranges.iter())
+            .map(|&[start_line, start_col, end_line, end_col]| {
+                let start = Cursor { line: start_line, col: start_col };
+                let end = Cursor { line: end_line, col: end_col };
+                if (start.line, start.col) <= (end.line, end.col) {
+                    Selection { start, end }
+                } else {
+                    Selection { start: end, end: start }
+                }
+            })
+            .collect();
+
+        // The text of the cursor line may have changed.
+        self.scroll_to_cursor();
+    }
+
+    pub fn set_cursor(&mut self, cursor: Cursor) {
+        self.cursor = cursor;
+        self.scroll_to_cursor();
+    }
+
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.width = width;
+        self.height = height;
+        self.scroll_to_cursor();
+    }
+
+    /// Returns the first line and column displayed.
+    pub fn scroll_offset(&self) -> (usize, usize) {
+        (self.first_line, self.first_col)
+    }
+
+    /// Restores the cursor and the scroll offset saved in a session, before
+    /// xi-core moves the cursor there: the view then keeps the offset.
+    pub fn restore_position(&mut self, cursor: Cursor, first_line: usize, first_col: usize) {
+        self.cursor = cursor;
+        self.first_line = first_line;
+        self.first_col = first_col;
+    }
+
+    /// Returns true if xi-core wraps the lines of the view.
+    pub fn is_wrapped(&self) -> bool {
+        matches!(self.wrap_width, Some(width) if width > 0)
+    }
+
+    /// Returns the lines xi-core must send, as the `[first, last)` region
+    /// expected by the `scroll` edit method, if it changed since the last
+    /// call.
+    pub fn scroll_region_change(&mut self) -> Option<(usize, usize)> {
+        let region = (self.first_line, self.first_line + self.height);
+        if self.scroll_region == Some(region) {
+            return None;
+        }
+
+        self.scroll_region = Some(region);
+        Some(region)
+    }
+
+    /// Scrolls the view so the primary cursor is visible.
+    fn scroll_to_cursor(&mut self) {
+        if self.cursor.line < self.first_line {
+            self.first_line = self.cursor.line;
+        } else if self.height > 0 && self.cursor.line >= self.first_line + self.height {
+            self.first_line = self.cursor.line + 1 - self.height;
+        }
+
+        // The wrapped lines always fit in the screen.
+        if self.is_wrapped() {
+            self.first_col = 0;
+            return;
+        }
+
+        let column = match self.lines.get(self.cursor.line) {
+            Some(line) => render::display_column(line, self.cursor.col, self.settings.tab_size()),
+            None => return,
+        };
+
+        if column < self.first_col {
+            self.first_col = column;
+        } else if self.width > 0 && column >= self.first_col + self.width {
+            self.first_col = column + 1 - self.width;
+        }
+    }
+
+    fn is_selected(&self, line: usize, col: usize) -> bool {
+        self.selections.iter().any(|selection| {
+            (selection.start.line, selection.start.col) <= (line, col)
+                && (line, col) < (selection.end.line, selection.end.col)
+        })
+    }
+
+    /// Returns the position of the first non blank character of the cursor
+    /// line.
+    pub fn first_non_blank(&self) -> Option<Cursor> {
+        let line = self.lines.get(self.cursor.line)?;
+        let col = line
+            .text
+            .char_indices()
+            .find(|(_, c)| !c.is_whitespace())
+            .map(|(col, _)| col)
+            .unwrap_or_else(|| line.text.trim_end_matches('\n').len());
+
+        Some(Cursor {
+            line: self.cursor.line,
+            col,
+        })
+    }
+
+    /// Returns the position of the bracket matching the one under the
+    /// cursor, or the first one after the cursor on its line.
+    ///
+    /// Only the lines known by the frontend are searched.
+    pub fn matching_bracket(&self) -> Option<Cursor> {
+        let line = self.lines.get(self.cursor.line)?;
+        let (col, bracket) = line
+            .text
+            .get(self.cursor.col..)?
+            .char_indices()
+            .find(|(_, c)| BRACKETS.iter().any(|(open, close)| c == open || c == close))
+            .map(|(idx, c)| (self.cursor.col + idx, c))?;
+
+        let &(open, close) = BRACKETS
+            .iter()
+            .find(|(open, close)| bracket == *open || bracket == *close)?;
+
+        let mut depth = 0;
+        if bracket == open {
+            for line_idx in self.cursor.line..self.lines.height() {
+                let text = &self.lines.get(line_idx)?.text;
+                let start = if line_idx == self.cursor.line { col } else { 0 };
+
+                for (idx, c) in text[start..].char_indices() {
+                    if c == open {
+                        depth += 1;
+                    } else if c == close {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(Cursor {
+                                line: line_idx,
+                                col: start + idx,
+                            });
+                        }
+                    }
+                }
+            }
+        } else {
+            for line_idx in (0..=self.cursor.line).rev() {
+                let text = &self.lines.get(line_idx)?.text;
+                let end = if line_idx == self.cursor.line {
+                    col + close.len_utf8()
+                } else {
+                    text.len()
+                };
+
+                for (idx, c) in text[..end].char_indices().rev() {
+                    if c == close {
+                        depth += 1;
+                    } else if c == open {
+                        depth -= 1;
+                        if depth == 0 {
+                            return Some(Cursor { line: line_idx, col: idx });
+                        }
+                    }
+                }
+            }
+        }
+
+        None
+    }
+}
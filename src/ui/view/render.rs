@@ -0,0 +1,349 @@
+use unicode_width::UnicodeWidthChar;
+
+use super::{Line, View};
+use crate::config::{Config, LineNumbers};
+use crate::ui::style::Styles;
+use crate::ui::terminal::{Style, Terminal};
+
+/// Marks the rows after the end of the document.
+const EMPTY_ROW: char = '~';
+
+/// Displayed in place of the control characters.
+const CONTROL_CHAR: char = '?';
+
+/// What is displayed for one character of a line.
+#[derive(Debug, Clone, Eq, PartialEq)]
+struct Cell {
+    /// The character and the zero-width characters combined with it.
+    text: String,
+    /// The number of columns taken on the screen.
+    width: usize,
+    style: Style,
+}
+
+impl View {
+    /// Renders the visible lines of the view on the first rows of the
+    /// terminal.
+    pub fn render(&self, terminal: &mut dyn Terminal, config: &Config, styles: &Styles) {
+        let (width, _) = terminal.size();
+        let gutter_width = self.gutter_width(config.line_numbers).min(width);
+        let tab_size = self.settings.tab_size();
+        let cursor_ln = self.lines.line_number(self.cursor.line);
+        let mut cursor_position = None;
+
+        for row in 0..self.height {
+            let line_idx = self.first_line + row;
+            let line = self.lines.get(line_idx);
+
+            let cells = match line {
+                Some(line) => {
+                    if line_idx == self.cursor.line {
+                        let column = display_column(line, self.cursor.col, tab_size);
+                        let x = gutter_width as usize + column.saturating_sub(self.first_col);
+                        cursor_position = Some((x as u16, row as u16));
+                    }
+                    self.line_cells(line_idx, line, styles)
+                }
+                None if line_idx >= self.lines.height() => vec![Cell {
+                    text: EMPTY_ROW.to_string(),
+                    width: 1,
+                    style: styles.base(),
+                }],
+                // The line is not known yet, xi-core will send it soon.
+                None => Vec::new(),
+            };
+
+            if gutter_width > 0 {
+                let number = line
+                    .and_then(|line| line_number(line, cursor_ln, config.line_numbers))
+                    .map(|number| number.to_string())
+                    .unwrap_or_default();
+                let gutter = format!(
+                    "{:>width$} ",
+                    number,
+                    width = gutter_width as usize - 1
+                );
+                terminal.draw(0, row as u16, &gutter, styles.base());
+            }
+
+            // The `~` of the empty rows is never scrolled.
+            let first_col = if line.is_some() { self.first_col } else { 0 };
+            draw_row(
+                terminal,
+                gutter_width,
+                row as u16,
+                width - gutter_width,
+                first_col,
+                &cells,
+                styles.base(),
+            );
+        }
+
+        terminal.set_cursor(cursor_position);
+    }
+
+    /// Returns the width of the gutter, large enough for the number of the
+    /// last known line of the document and a separating space.
+    pub fn gutter_width(&self, line_numbers: LineNumbers) -> u16 {
+        if line_numbers == LineNumbers::Off {
+            return 0;
+        }
+
+        let digits = self.lines.last_line_number().unwrap_or(1).to_string().len();
+        digits as u16 + 1
+    }
+
+    /// Returns the cells displaying a line.
+    ///
+    /// The selections and the cursors other than the primary one, displayed
+    /// by the terminal itself, are drawn in reverse video over the style
+    /// given by xi-core.
+    fn line_cells(&self, line_idx: usize, line: &Line, styles: &Styles) -> Vec<Cell> {
+        let text = visible_text(line);
+        let spans = style_spans(line, styles);
+        let tab_size = self.settings.tab_size();
+        let is_extra_cursor = |col: usize| {
+            line.cursor.contains(&col) && (line_idx != self.cursor.line || col != self.cursor.col)
+        };
+
+        let mut cells: Vec<Cell> = Vec::new();
+        let mut column = 0;
+
+        for (col, c) in text.char_indices() {
+            let mut style = spans
+                .iter()
+                .rev()
+                .find(|(start, end, _)| *start <= col && col < *end)
+                .map(|(_, _, style)| *style)
+                .unwrap_or_else(|| styles.base());
+            if self.is_selected(line_idx, col) || is_extra_cursor(col) {
+                style.reverse = true;
+            }
+
+            let width = char_width(c, column, tab_size);
+            if width == 0 {
+                if let Some(cell) = cells.last_mut() {
+                    cell.text.push(c);
+                }
+                continue;
+            }
+
+            let text = match c {
+                '\t' => " ".repeat(width),
+                c if c.is_control() => CONTROL_CHAR.to_string(),
+                c => c.to_string(),
+            };
+
+            cells.push(Cell { text, width, style });
+            column += width;
+        }
+
+        if is_extra_cursor(text.len()) {
+            cells.push(Cell {
+                text: String::from(" "),
+                width: 1,
+                style: Style {
+                    reverse: true,
+                    ..styles.base()
+                },
+            });
+        }
+
+        cells
+    }
+}
+
+/// Returns the number displayed in the gutter for a line, if any, the
+/// cursor being on the logical line `cursor_ln`.
+///
+/// The rows continuing a wrapped line have no number. The absolute numbers
+/// are shown until the line of the cursor is known.
+fn line_number(line: &Line, cursor_ln: Option<usize>, line_numbers: LineNumbers) -> Option<usize> {
+    let ln = line.ln?;
+    let distance = match cursor_ln {
+        Some(cursor_ln) => ln.abs_diff(cursor_ln),
+        None if line_numbers == LineNumbers::Off => return None,
+        None => return Some(ln),
+    };
+
+    match line_numbers {
+        LineNumbers::Off => None,
+        LineNumbers::Absolute => Some(ln),
+        LineNumbers::Relative => Some(distance),
+        LineNumbers::Hybrid if distance == 0 => Some(ln),
+        LineNumbers::Hybrid => Some(distance),
+    }
+}
+
+/// Returns the text of a line without its line ending.
+fn visible_text(line: &Line) -> &str {
+    line.text.trim_end_matches(&['\n', '\r'][..])
+}
+
+/// Returns the byte ranges of a line with the style applied to each of
+/// them, the later ranges taking precedence.
+fn style_spans(line: &Line, styles: &Styles) -> Vec<(usize, usize, Style)> {
+    let mut spans = Vec::new();
+    let mut end: i64 = 0;
+
+    for span in line.styles.chunks(3) {
+        if let [start, length, id] = *span {
+            let start = end + start;
+            end = start + length;
+            if let Some(style) = styles.get(id as u64) {
+                spans.push((start.max(0) as usize, end.max(0) as usize, style));
+            }
+        }
+    }
+
+    spans
+}
+
+/// Returns the number of columns taken by a character displayed at the
+/// given column, the tabs going to the next multiple of `tab_size`.
+fn char_width(c: char, column: usize, tab_size: usize) -> usize {
+    match c {
+        '\t' => tab_size - column % tab_size,
+        c if c.is_control() => 1,
+        // The combining characters have no width, they are drawn over the
+        // previous character.
+        c => c.width().unwrap_or(0),
+    }
+}
+
+/// Returns the number of columns taken by `text` displayed from the first
+/// column.
+pub fn text_width(text: &str, tab_size: usize) -> usize {
+    text.chars()
+        .fold(0, |column, c| column + char_width(c, column, tab_size))
+}
+
+/// Returns the column on the screen of the byte offset `col` of a line,
+/// before any horizontal scrolling.
+pub fn display_column(line: &Line, col: usize, tab_size: usize) -> usize {
+    visible_text(line)
+        .char_indices()
+        .take_while(|(idx, _)| *idx < col)
+        .fold(0, |column, (_, c)| column + char_width(c, column, tab_size))
+}
+
+/// Draws the cells on a row from the column `x`, skipping the `first_col`
+/// first columns of the line, and clears the rest of the `width` columns
+/// with the `background` style.
+///
+/// A wide character cut by an edge of the screen is replaced by spaces.
+fn draw_row(
+    terminal: &mut dyn Terminal,
+    x: u16,
+    row: u16,
+    width: u16,
+    first_col: usize,
+    cells: &[Cell],
+    background: Style,
+) {
+    let width = width as usize;
+    let mut column = 0;
+    let mut drawn = 0;
+    let mut run = String::new();
+    let mut run_width = 0;
+    let mut run_style = Style::default();
+
+    for cell in cells {
+        let start = column;
+        column += cell.width;
+        if column <= first_col {
+            continue;
+        }
+
+        let (text, cell_width) = if start < first_col {
+            let visible = column - first_col;
+            (" ".repeat(visible), visible)
+        } else {
+            (cell.text.clone(), cell.width)
+        };
+
+        let (text, cell_width) = if drawn + run_width + cell_width > width {
+            let visible = width - drawn - run_width;
+            (" ".repeat(visible), visible)
+        } else {
+            (text, cell_width)
+        };
+
+        if cell.style != run_style && !run.is_empty() {
+            terminal.draw(x + drawn as u16, row, &run, run_style);
+            drawn += run_width;
+            run.clear();
+            run_width = 0;
+        }
+
+        run_style = cell.style;
+        run.push_str(&text);
+        run_width += cell_width;
+
+        if drawn + run_width >= width {
+            break;
+        }
+    }
+
+    if !run.is_empty() {
+        terminal.draw(x + drawn as u16, row, &run, run_style);
+        drawn += run_width;
+    }
+
+    if drawn < width {
+        terminal.draw(x + drawn as u16, row, &" ".repeat(width - drawn), background);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    /// Returns a view of `count` logical lines, each wrapped on two rows.
+    fn wrapped_view(count: usize) -> View {
+        let lines: Vec<Value> = (1..=count)
+            .flat_map(|ln| {
+                let first = json!({ "text": "wrapped ", "ln": ln });
+                vec![first, json!({ "text": "line\n" })]
+            })
+            .collect();
+        let update = json!({ "ops": [{ "op": "ins", "n": lines.len(), "lines": lines }] });
+
+        let mut view = View::default();
+        view.update(&serde_json::from_value(update).unwrap());
+        view
+    }
+
+    #[test]
+    fn sizes_the_gutter_from_the_logical_lines() {
+        let view = wrapped_view(9);
+        assert_eq!(view.gutter_width(LineNumbers::Absolute), 2);
+        assert_eq!(view.gutter_width(LineNumbers::Off), 0);
+        assert_eq!(wrapped_view(10).gutter_width(LineNumbers::Relative), 3);
+    }
+
+    #[test]
+    fn counts_the_logical_lines_from_the_cursor() {
+        let view = wrapped_view(4);
+        // The cursor is on the row continuing the second line.
+        let cursor_ln = view.lines.line_number(3);
+        assert_eq!(cursor_ln, Some(2));
+
+        let numbers = |line_numbers| -> Vec<Option<usize>> {
+            (0..8)
+                .map(|idx| line_number(view.lines.get(idx).unwrap(), cursor_ln, line_numbers))
+                .collect()
+        };
+        assert_eq!(
+            numbers(LineNumbers::Relative),
+            vec![Some(1), None, Some(0), None, Some(1), None, Some(2), None]
+        );
+        assert_eq!(
+            numbers(LineNumbers::Hybrid),
+            vec![Some(1), None, Some(2), None, Some(1), None, Some(2), None]
+        );
+        assert_eq!(line_number(view.lines.get(4).unwrap(), None, LineNumbers::Relative), Some(3));
+    }
+}
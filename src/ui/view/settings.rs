@@ -0,0 +1,65 @@
+use serde_json::Value;
+
+/// The number of columns between two tab stops when xi-core didn't send
+/// the `tab_size` of the view yet.
+const DEFAULT_TAB_SIZE: usize = 4;
+
+/// The settings of a view, as sent by xi-core in the `config_changed`
+/// notifications.
+///
+/// xi-core only sends the settings which changed, the missing ones are
+/// `None` until their first change.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Settings {
+    pub tab_size: Option<usize>,
+    pub translate_tabs_to_spaces: Option<bool>,
+    pub use_tab_stops: Option<bool>,
+    pub autodetect_whitespace: Option<bool>,
+    pub auto_indent: Option<bool>,
+    pub font_face: Option<String>,
+    pub font_size: Option<f64>,
+    pub line_ending: Option<String>,
+    pub wrap_width: Option<usize>,
+    pub word_wrap: Option<bool>,
+    pub scroll_past_end: Option<bool>,
+    pub surrounding_pairs: Option<Vec<(String, String)>>,
+}
+
+impl Settings {
+    /// Applies the `changes` param of a `config_changed` notification.
+    pub fn apply(&mut self, changes: &Value) {
+        let settings = match (serde_json::to_value(&*self), changes) {
+            (Ok(Value::Object(mut settings)), Value::Object(changed)) => {
+                settings.extend(changed.clone());
+                settings
+            }
+            _ => {
+                error!("invalid config changes: {}", changes);
+                return;
+            }
+        };
+
+        match serde_json::from_value(Value::Object(settings)) {
+            Ok(settings) => *self = settings,
+            Err(err) => error!("invalid config changes {}: {}", changes, err),
+        }
+    }
+
+    pub fn tab_size(&self) -> usize {
+        match self.tab_size {
+            Some(tab_size) if tab_size > 0 => tab_size,
+            _ => DEFAULT_TAB_SIZE,
+        }
+    }
+
+    /// Returns the value of the setting `key`, `None` if it is unknown or
+    /// was never sent by xi-core.
+    pub fn get(&self, key: &str) -> Option<Value> {
+        match serde_json::to_value(self) {
+            Ok(Value::Object(mut settings)) => {
+                settings.remove(key).filter(|value| !value.is_null())
+            }
+            _ => None,
+        }
+    }
+}